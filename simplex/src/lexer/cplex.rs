@@ -0,0 +1,137 @@
+use crate::lexer::tokens::{ArithOperation, CmpOperation};
+
+/// Tokens of the CPLEX LP format. Section keywords are recognized per line
+/// by the parser, everything else is split into these.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LpToken {
+    Name(String),
+    Num(f64),
+    /// only `+` and `-`
+    Op(ArithOperation),
+    /// `<` and `=<` read as `<=`, `>` and `=>` as `>=`
+    Cmp(CmpOperation),
+    Colon,
+}
+
+/// Characters besides letters that may start a name
+const NAME_SYMBOLS: &str = "!\"#$%&()/,;?@_`'{}|~";
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || NAME_SYMBOLS.contains(c)
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '.'
+}
+
+/// Whether `name` reads back as a single name token
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_name_start) && chars.all(is_name_char)
+}
+
+/// Split one line into tokens, a `\` starts a comment that runs to the end
+/// of the line
+pub fn tokenize_lp(line: &str) -> anyhow::Result<Vec<LpToken>> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\\' => break,
+            c if c.is_whitespace() => i += 1,
+            '+' => {
+                tokens.push(LpToken::Op(ArithOperation::Add));
+                i += 1;
+            }
+            '-' => {
+                tokens.push(LpToken::Op(ArithOperation::Sub));
+                i += 1;
+            }
+            ':' => {
+                tokens.push(LpToken::Colon);
+                i += 1;
+            }
+            '<' | '>' | '=' => {
+                let (op, len) = match (c, next) {
+                    ('<', Some('=')) | ('=', Some('<')) => (CmpOperation::Leq, 2),
+                    ('>', Some('=')) | ('=', Some('>')) => (CmpOperation::Geq, 2),
+                    ('<', _) => (CmpOperation::Leq, 1),
+                    ('>', _) => (CmpOperation::Geq, 1),
+                    _ => (CmpOperation::Eq, 1),
+                };
+                tokens.push(LpToken::Cmp(op));
+                i += len;
+            }
+            c if c.is_ascii_digit() || (c == '.' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // an exponent needs digits, `2e` followed by a letter is 2 times e
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let s = chars[start..i].iter().collect::<String>();
+                let n = s
+                    .parse::<f64>()
+                    .map_err(|_| anyhow::anyhow!("'{}' is not a number", s))?;
+                tokens.push(LpToken::Num(n));
+            }
+            c if is_name_start(c) => {
+                let start = i;
+                while i < chars.len() && is_name_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(LpToken::Name(chars[start..i].iter().collect()));
+            }
+            c => anyhow::bail!("unexpected character '{}'", c),
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tokenize_lp, LpToken};
+    use crate::lexer::tokens::{ArithOperation, CmpOperation};
+
+    #[test]
+    fn test_tokenize_lp() {
+        let tokens = tokenize_lp(" c1: 3x1 - 2.5e1 y_2 + .5 e =< -4 \\ comment").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                LpToken::Name(String::from("c1")),
+                LpToken::Colon,
+                LpToken::Num(3.0),
+                LpToken::Name(String::from("x1")),
+                LpToken::Op(ArithOperation::Sub),
+                LpToken::Num(25.0),
+                LpToken::Name(String::from("y_2")),
+                LpToken::Op(ArithOperation::Add),
+                LpToken::Num(0.5),
+                LpToken::Name(String::from("e")),
+                LpToken::Cmp(CmpOperation::Leq),
+                LpToken::Op(ArithOperation::Sub),
+                LpToken::Num(4.0),
+            ]
+        );
+        assert_eq!(
+            tokenize_lp("x >= 1 y > 2 z = 3").unwrap()[4],
+            LpToken::Cmp(CmpOperation::Geq)
+        );
+        assert!(tokenize_lp("x * y").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use self::tokens::LexState;
 
+pub mod cplex;
 pub mod token_automata;
 pub mod tokenizer;
 pub mod tokens;
@@ -3,8 +3,6 @@ use super::{
     Tokenable,
 };
 
-// TODO: Add support for comments
-
 #[derive(Debug)]
 pub struct TAVariable {
     coef: Option<TANum>,
@@ -48,16 +46,26 @@ pub struct TACmp {
 
 #[derive(Debug)]
 pub struct TAFun {
-    fun: Option<String>,
-    last_char: Option<char>,
+    fun: String,
+    dead: bool,
+}
+
+/// Keywords of the language, read as `Token::Fun`
+const KEYWORDS: [&str; 7] = ["max", "min", "st", "bounds", "free", "int", "bin"];
+
+/// `name:` in front of a constraint or objective, the colon has to follow
+/// the name directly
+#[derive(Debug)]
+pub struct TALabel {
+    name: Option<String>,
+    colon: bool,
     dead: bool,
 }
 
 impl TAFun {
     pub fn new() -> Self {
         Self {
-            fun: None,
-            last_char: None,
+            fun: String::new(),
             dead: false,
         }
     }
@@ -68,42 +76,27 @@ impl Tokenable for TAFun {
         if self.dead {
             return LexState::NoMatch;
         }
-        if self.last_char.is_none() && (c == 'm' || c == 's') {
-            self.last_char = Some(c);
-            return LexState::Match;
-        }
-        if self.last_char == Some('s') && c == 't' {
-            self.last_char = Some(c);
-            self.fun = Some(String::from("st"));
+        self.fun.push(c);
+        if KEYWORDS.contains(&self.fun.as_str()) {
             return LexState::Final;
         }
-        if self.last_char == Some('m') && (c == 'a' || c == 'i') {
-            self.last_char = Some(c);
+        if KEYWORDS.iter().any(|k| k.starts_with(&self.fun)) {
             return LexState::Match;
         }
-        if self.last_char == Some('a') && c == 'x' {
-            self.fun = Some(String::from("max"));
-            return LexState::Final;
-        }
-        if self.last_char == Some('i') && c == 'n' {
-            self.fun = Some(String::from("min"));
-            return LexState::Final;
-        }
 
         self.dead = true;
         LexState::NoMatch
     }
     fn reset(&mut self) {
-        self.fun = None;
+        self.fun.clear();
         self.dead = false;
-        self.last_char = None;
     }
     fn tokenize(&self) -> Option<super::tokens::Token> {
-        if self.dead {
+        if self.dead || !KEYWORDS.contains(&self.fun.as_str()) {
             return None;
         }
 
-        self.fun.as_ref().map(|s| Token::Fun(s.clone()))
+        Some(Token::Fun(self.fun.clone()))
     }
 }
 
@@ -140,10 +133,10 @@ impl Tokenable for TACmp {
                     }
                 } else if self.cmp.is_none() {
                     self.cmp = Some(CmpOperation::Eq);
-                    return LexState::Final;
+                    LexState::Final
                 } else {
                     self.dead = true;
-                    return LexState::NoMatch;
+                    LexState::NoMatch
                 }
             }
             '<' => {
@@ -210,14 +203,9 @@ impl Tokenable for TANum {
                 }
                 LexState::Final
             }
-            '.' => {
-                if self.int_part && self.num.is_some() {
-                    self.int_part = false;
-                    LexState::Final
-                } else {
-                    self.dead = true;
-                    LexState::NoMatch
-                }
+            '.' if self.int_part && self.num.is_some() => {
+                self.int_part = false;
+                LexState::Final
             }
             '-' => {
                 if self.num.is_none() && self.signess == 1 {
@@ -316,9 +304,11 @@ impl Tokenable for TAVariable {
                 _ => return LexState::Match,
             }
         }
-        if c.is_numeric() && self.name.is_some() {
-            self.name.as_mut().unwrap().push(c);
-            return LexState::Final;
+        if let Some(name) = self.name.as_mut() {
+            if c.is_numeric() {
+                name.push(c);
+                return LexState::Final;
+            }
         }
 
         LexState::NoMatch
@@ -459,10 +449,60 @@ impl Tokenable for TARParan {
     }
 }
 
+impl TALabel {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            colon: false,
+            dead: false,
+        }
+    }
+}
+
+impl Tokenable for TALabel {
+    fn consume_char(&mut self, c: char) -> LexState {
+        if self.dead || self.colon {
+            self.dead = true;
+            return LexState::NoMatch;
+        }
+        match (&mut self.name, c) {
+            (None, c) if c.is_alphabetic() || c == '_' => {
+                self.name = Some(String::from(c));
+                LexState::Match
+            }
+            (Some(name), c) if c.is_alphanumeric() || c == '_' => {
+                name.push(c);
+                LexState::Match
+            }
+            (Some(_), ':') => {
+                self.colon = true;
+                LexState::Final
+            }
+            _ => {
+                self.dead = true;
+                LexState::NoMatch
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.dead = false;
+        self.colon = false;
+        self.name = None;
+    }
+
+    fn tokenize(&self) -> Option<super::tokens::Token> {
+        if self.dead || !self.colon {
+            return None;
+        }
+        self.name.as_ref().map(|name| Token::Label(name.clone()))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::lexer::{
-        token_automata::{TAArithOp, TACmp, TAFun, TALParan, TANum, TARParan, TAVariable},
+        token_automata::{TAArithOp, TACmp, TAFun, TALParan, TALabel, TANum, TARParan, TAVariable},
         tokens::{ArithOperation, CmpOperation, Token, F64},
         Tokenable,
     };
@@ -526,7 +566,7 @@ mod test {
             let automata: &mut dyn Tokenable = &mut automata;
             let t = tokenize(automata, s).unwrap();
             if let Token::Variable(s, n) = t {
-                assert!(s == String::from("xa_1"));
+                assert!(s == "xa_1");
                 assert!(F64(-1.209) == n);
             } else {
                 panic!("Not a variable");
@@ -739,5 +779,43 @@ mod test {
             let automata: &mut dyn Tokenable = &mut automata;
             assert!(tokenize(automata, s).is_none());
         }
+        for s in ["bounds", "free", "int", "bin"] {
+            let mut automata = TAFun::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s) == Some(Token::Fun(String::from(s))));
+        }
+        {
+            let s = "bound";
+            let mut automata = TAFun::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s).is_none());
+        }
+    }
+    #[test]
+    fn test_label() {
+        {
+            let s = "capacity_a:";
+            let mut automata = TALabel::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s) == Some(Token::Label(String::from("capacity_a"))));
+        }
+        {
+            let s = "capacity_a";
+            let mut automata = TALabel::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s).is_none());
+        }
+        {
+            let s = "1a:";
+            let mut automata = TALabel::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s).is_none());
+        }
+        {
+            let s = "a::";
+            let mut automata = TALabel::new();
+            let automata: &mut dyn Tokenable = &mut automata;
+            assert!(tokenize(automata, s).is_none());
+        }
     }
 }
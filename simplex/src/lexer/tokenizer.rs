@@ -1,7 +1,7 @@
 use std::io::BufRead;
 
 use super::{
-    token_automata::{TAArithOp, TACmp, TAFun, TALParan, TANum, TARParan, TAVariable},
+    token_automata::{TAArithOp, TACmp, TAFun, TALParan, TALabel, TANum, TARParan, TAVariable},
     tokens::{LexState, Token},
     Tokenable,
 };
@@ -33,6 +33,10 @@ pub struct TokenizerIterator<R: BufRead> {
     current_line_number: u32,
     pointer: usize,
     state_machine: Vec<Box<dyn Tokenable>>,
+    /// line where the `/* */` we are in started, it may span lines
+    block_comment: Option<u32>,
+    /// error that ended lexing early, see `error`
+    error: Option<String>,
 
     peek_buffer: Option<Token>,
 }
@@ -46,13 +50,19 @@ impl<R: BufRead> TokenizerIterator<R> {
         let ta_rparen: Box<dyn Tokenable> = Box::new(TARParan::new());
         let ta_func: Box<dyn Tokenable> = Box::new(TAFun::new());
         let ta_cmp: Box<dyn Tokenable> = Box::new(TACmp::new());
+        let ta_label: Box<dyn Tokenable> = Box::new(TALabel::new());
 
         Self {
             tokenizer,
             current_line_string: None,
             current_line_number: 1,
             pointer: 0,
-            state_machine: vec![ta_op, ta_lparen, ta_rparen, ta_cmp, ta_func, ta_num, ta_var],
+            // a label has to come after the variable, which is a prefix of it
+            state_machine: vec![
+                ta_op, ta_lparen, ta_rparen, ta_cmp, ta_func, ta_num, ta_var, ta_label,
+            ],
+            block_comment: None,
+            error: None,
             peek_buffer: None,
         }
     }
@@ -63,6 +73,12 @@ impl<R: BufRead> TokenizerIterator<R> {
         }
     }
 
+    /// Error the input could not be lexed with. The iterator simply ends
+    /// then, so the caller has to check this after the last token.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
     pub fn raise_parsing_error(&self, msg: &str) {
         eprintln!(
             "Parsing error at line {}: {}\nError:{}\n",
@@ -135,11 +151,43 @@ impl<R: BufRead> TokenizerIterator<R> {
                 Some(current_line_string) => {
                     match &current_line_string.chars().nth(self.pointer) {
                         Some(c) => {
-                            if *c == '\n' && states.is_empty() {
-                                self.current_line_number += 1;
+                            // comments are only recognized between tokens, a
+                            // token right before a comment is finished first
+                            let next = current_line_string.chars().nth(self.pointer + 1);
+                            if self.block_comment.is_some() && *c != '\n' {
+                                if *c == '*' && next == Some('/') {
+                                    self.block_comment = None;
+                                    self.pointer += 1;
+                                }
+                                self.pointer += 1;
+                                continue;
+                            }
+                            if states.is_empty() && *c == '/' && next == Some('*') {
+                                self.block_comment = Some(self.current_line_number);
+                                self.pointer += 2;
+                                continue;
+                            }
+                            if states.is_empty() && (*c == '#' || (*c == '/' && next == Some('/')))
+                            {
+                                // skip to the line break, which still ends the line
+                                self.pointer = current_line_string.chars().count() - 1;
+                                if !current_line_string.ends_with('\n') {
+                                    self.pointer += 1;
+                                }
+                                continue;
+                            }
+                            // `;` separates statements on one line
+                            if (*c == '\n' || *c == ';') && states.is_empty() {
+                                if *c == '\n' {
+                                    self.current_line_number += 1;
+                                }
                                 self.pointer += 1;
                                 return Some(Token::EOL);
                             }
+                            if *c == ',' && states.is_empty() {
+                                self.pointer += 1;
+                                return Some(Token::Comma);
+                            }
                             if (*c == '\r' || *c == ' ' || *c == '\t' || *c == '\n')
                                 && states.is_empty()
                             {
@@ -151,12 +199,16 @@ impl<R: BufRead> TokenizerIterator<R> {
                             let current_states = self.step(*c);
                             if !states.is_empty() {
                                 let mut return_token = None;
+                                let mut longer = false;
                                 for (i, (current_matcher, _t)) in current_states.iter().enumerate()
                                 {
                                     let (prev_matcher, prev_t) = &states[i];
 
+                                    // on a tie the first automaton wins, so
+                                    // keywords come before variables
                                     if *current_matcher == LexState::NoMatch
                                         && *prev_matcher == LexState::Final
+                                        && return_token.is_none()
                                     {
                                         // skip whitespace etc
                                         if *c == ' ' || *c == '\t' {
@@ -169,11 +221,11 @@ impl<R: BufRead> TokenizerIterator<R> {
                                     if *current_matcher != LexState::NoMatch
                                         && *prev_matcher != LexState::NoMatch
                                     {
-                                        return_token = None; // longer Match possible
+                                        longer = true; // longer Match possible
                                     }
                                 }
 
-                                if let Some(return_token) = return_token {
+                                if let (Some(return_token), false) = (return_token, longer) {
                                     // println!("return: {:?}", return_token);
                                     return return_token;
                                 }
@@ -194,6 +246,12 @@ impl<R: BufRead> TokenizerIterator<R> {
                             }
                             self.current_line_string = None;
                             self.pointer = 0;
+                            if let Some(line) = self.block_comment.take() {
+                                let msg = format!("unterminated block comment from line {}", line);
+                                self.raise_parsing_error(&msg);
+                                self.error = Some(msg);
+                                return None;
+                            }
 
                             for (matcher, token) in &states {
                                 if LexState::Final == *matcher {
@@ -325,4 +383,102 @@ st {
             assert!(tokens.len() == truth.len());
         }
     }
+
+    #[test]
+    fn test_tokenizer_comments() {
+        let input = "# heading
+max { x1 } // objective
+/* between
+   the blocks */ st { x1 <= 4# no space
+    x1 >= 1 /* inline */ }
+";
+        let tokens = super::Tokenizer::new(input.as_bytes())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let truth = vec![
+            Token::EOL,
+            Token::Fun("max".to_string()),
+            Token::LParen('{'),
+            Token::Variable("x1".to_string(), F64(1.0)),
+            Token::RParen('}'),
+            Token::EOL,
+            Token::EOL,
+            Token::Fun("st".to_string()),
+            Token::LParen('{'),
+            Token::Variable("x1".to_string(), F64(1.0)),
+            Token::Cmp(CmpOperation::Leq),
+            Token::Num(F64(4.0)),
+            Token::EOL,
+            Token::Variable("x1".to_string(), F64(1.0)),
+            Token::Cmp(CmpOperation::Geq),
+            Token::Num(F64(1.0)),
+            Token::RParen('}'),
+            Token::EOL,
+        ];
+        assert_eq!(tokens, truth);
+
+        // an open comment swallows the rest and fails
+        let mut iterator =
+            super::Tokenizer::new("max{x} st{x<=4} /* open\n x".as_bytes()).into_iter();
+        assert_eq!(iterator.by_ref().count(), 11);
+        assert_eq!(
+            iterator.error(),
+            Some("unterminated block comment from line 1")
+        );
+
+        // a division is not a comment
+        let tokens = super::Tokenizer::new("x / 2".as_bytes())
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(tokens[1], Token::ArithOp(ArithOperation::Div));
+    }
+
+    #[test]
+    fn test_tokenizer_keywords() {
+        let tokens = super::Tokenizer::new("max{x} bounds { x free; y >= -5 }".as_bytes())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let truth = vec![
+            Token::Fun("max".to_string()),
+            Token::LParen('{'),
+            Token::Variable("x".to_string(), F64(1.0)),
+            Token::RParen('}'),
+            Token::Fun("bounds".to_string()),
+            Token::LParen('{'),
+            Token::Variable("x".to_string(), F64(1.0)),
+            Token::Fun("free".to_string()),
+            Token::EOL,
+            Token::Variable("y".to_string(), F64(1.0)),
+            Token::Cmp(CmpOperation::Geq),
+            Token::Num(F64(-5.0)),
+            Token::RParen('}'),
+        ];
+        assert_eq!(tokens, truth);
+
+        let tokens = super::Tokenizer::new("int { x_1,x_2 }".as_bytes())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let truth = vec![
+            Token::Fun("int".to_string()),
+            Token::LParen('{'),
+            Token::Variable("x_1".to_string(), F64(1.0)),
+            Token::Comma,
+            Token::Variable("x_2".to_string(), F64(1.0)),
+            Token::RParen('}'),
+        ];
+        assert_eq!(tokens, truth);
+
+        // keywords only match as a whole
+        let tokens = super::Tokenizer::new("freed bound binary".as_bytes())
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Variable("freed".to_string(), F64(1.0)),
+                Token::Variable("bound".to_string(), F64(1.0)),
+                Token::Variable("binary".to_string(), F64(1.0)),
+            ]
+        );
+    }
 }
@@ -24,15 +24,30 @@ pub enum CmpOperation {
     Geq,
 }
 
+impl CmpOperation {
+    /// Relation with both sides swapped, `a <= b` is `b >= a`
+    pub fn reversed(&self) -> Self {
+        match self {
+            CmpOperation::Lt => CmpOperation::Gt,
+            CmpOperation::Gt => CmpOperation::Lt,
+            CmpOperation::Leq => CmpOperation::Geq,
+            CmpOperation::Geq => CmpOperation::Leq,
+            CmpOperation::Eq => CmpOperation::Eq,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
     Variable(String, F64),   // Variable Name
     ArithOp(ArithOperation), // +, -, *, /
     LParen(char),            // (, [, {
     RParen(char),            // ), ], }
-    Fun(String),             // max, min, st
+    Fun(String),             // max, min, st, bounds, free, int, bin
     Num(F64),                // number
     Cmp(CmpOperation),       //
+    Label(String),           // name: of a constraint or objective
+    Comma,                   // , between names in a list
     EOL,
 }
 
@@ -47,7 +62,7 @@ pub enum LexState {
 mod test {
     use crate::lexer::tokens::F64;
     #[test]
-    fn test_F64_eq() {
+    fn test_f64_eq() {
         assert!(F64(-1.2089999) == F64(-1.209));
         assert!(F64(-1.2090001) == F64(-1.209));
     }
@@ -1,7 +1,583 @@
+// AST, RHS and EOL are kept as written in the grammar notes.
+#![allow(clippy::upper_case_acronyms)]
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process,
+};
+
+use mps::{reader::read_mps, writer::write_mps, MpsFormat};
+use parser::{
+    ast::construct_ast,
+    cplex::{read_lp, write_lp},
+};
+use semantics::{
+    branch_and_bound::{BranchAndBound, MipOptions, NodeSelection},
+    simplex::{Algorithm, InitStrategy, Method, PivotRule, SolverOptions},
+    solution::{OutputFormat, Status},
+    symbols::SymbolTable,
+    trace::TraceFormat,
+};
+
 mod lexer;
+mod mps;
 mod parser;
 mod semantics;
+#[cfg(test)]
+mod test_util;
+
+const USAGE: &str = "usage: simplex [--input FORMAT] [--format json|csv|text] [--write mps|cplex]
+               [--method tableau|revised] [--algorithm primal|dual]
+               [--init two-phase|big-m]
+               [--pivot-rule dantzig|bland|steepest-edge|lexicographic] [--gap GAP]
+               [--node-selection best-bound|depth-first] [--cut-rounds N]
+               [--max-nodes N] [--trace text|markdown|latex] [FILE]
+
+Solve the linear program in FILE, or stdin if FILE is missing or '-'.
+Programs with integer variables are solved by branch-and-bound.
+
+options:
+  -i, --input FORMAT   read the program as lp (the max { } st { } syntax), mps,
+                       fixed-mps or cplex, default: mps for *.mps files, cplex
+                       for *.lp files, lp otherwise
+  -f, --format FORMAT  print the solution as json, csv or text (default)
+  -w, --write FORMAT   print the program as free MPS or CPLEX LP instead of
+                       solving it
+  --method M           keep the dense tableau (default) or run the revised
+                       primal simplex on a factorized basis with Devex
+                       pricing, which scales to large programs; integer
+                       programs always use the tableau
+  --algorithm A        simplex for the relaxation, primal (default) or dual;
+                       dual falls back to primal without a dual feasible start
+                       and needs the tableau method
+  --init I             starting basis of the primal tableau simplex for rows
+                       without a slack: two-phase (default) or big-m, which
+                       penalizes the artificial variables with M = 1e6
+  --pivot-rule R       entering column: dantzig (default of the tableau),
+                       bland, steepest-edge (default of the revised simplex,
+                       which prices it with Devex) or lexicographic, which
+                       needs the tableau
+  --gap GAP            relative optimality gap of branch-and-bound, default: 1e-4
+  --node-selection S   order of the branch-and-bound nodes, best-bound (default)
+                       or depth-first
+  --cut-rounds N       rounds of Gomory cuts at the root before branching,
+                       default: 5
+  --max-nodes N        nodes branch-and-bound solves before it gives up with
+                       exit code 3, default: 100000
+  --trace FORMAT       print every tableau of the (root) simplex as text,
+                       markdown or latex before the solution; needs the
+                       tableau method and text output
+
+exit codes:
+  0  optimal
+  1  infeasible
+  2  unbounded
+  3  iteration or node limit reached
+  4  the program could not be read or parsed
+  5  invalid arguments
+  6  the program is invalid, e.g. a variable with an empty bound box
+  7  the solver failed on a numerical problem
+  8  the output could not be written";
+
+const EXIT_PARSE_ERROR: i32 = 4;
+const EXIT_USAGE: i32 = 5;
+const EXIT_MODEL_ERROR: i32 = 6;
+const EXIT_SOLVER_ERROR: i32 = 7;
+const EXIT_OUTPUT_ERROR: i32 = 8;
+
+/// Why `run` failed, picks the message and exit code
+#[derive(Debug)]
+enum Failure {
+    /// the program could not be read or parsed
+    Parse(anyhow::Error),
+    /// the program was read but cannot be solved or written as given
+    Model(anyhow::Error),
+    /// the solver gave up, which is a bug or a numerical problem
+    Solver(anyhow::Error),
+    /// stdout could not be written
+    Output(io::Error),
+}
+
+impl Failure {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Parse(_) => EXIT_PARSE_ERROR,
+            Failure::Model(_) => EXIT_MODEL_ERROR,
+            Failure::Solver(_) => EXIT_SOLVER_ERROR,
+            Failure::Output(_) => EXIT_OUTPUT_ERROR,
+        }
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Parse(err) => write!(f, "{}", err),
+            Failure::Model(err) => write!(f, "invalid program: {}", err),
+            Failure::Solver(err) => write!(f, "solver failed: {}", err),
+            Failure::Output(err) => write!(f, "cannot write the output: {}", err),
+        }
+    }
+}
+
+/// Syntax of the program read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputFormat {
+    Lp,
+    Mps(MpsFormat),
+    Cplex,
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "lp" => Ok(InputFormat::Lp),
+            "mps" => Ok(InputFormat::Mps(MpsFormat::Free)),
+            "fixed-mps" => Ok(InputFormat::Mps(MpsFormat::Fixed)),
+            "cplex" => Ok(InputFormat::Cplex),
+            _ => anyhow::bail!(
+                "unknown input '{}', expected lp, mps, fixed-mps or cplex",
+                s
+            ),
+        }
+    }
+}
+
+/// Format a program can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelFormat {
+    Mps,
+    Cplex,
+}
+
+impl std::str::FromStr for ModelFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "mps" => Ok(ModelFormat::Mps),
+            "cplex" => Ok(ModelFormat::Cplex),
+            _ => anyhow::bail!("unknown format '{}', expected mps or cplex", s),
+        }
+    }
+}
+
+/// Command line arguments
+#[derive(Debug, PartialEq)]
+enum Args {
+    Help,
+    Solve(Options),
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    /// `None` reads from stdin
+    input: Option<String>,
+    /// `None` picks by file extension
+    input_format: Option<InputFormat>,
+    format: OutputFormat,
+    /// print the program instead of solving it
+    write: Option<ModelFormat>,
+    method: Method,
+    algorithm: Algorithm,
+    init: InitStrategy,
+    /// `None` picks by method
+    pivot_rule: Option<PivotRule>,
+    /// relative optimality gap of branch-and-bound
+    gap: f64,
+    node_selection: NodeSelection,
+    cut_rounds: usize,
+    max_nodes: usize,
+    /// print the tableaus of the solver in this format
+    trace: Option<TraceFormat>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let mip = MipOptions::default();
+        Self {
+            input: None,
+            input_format: None,
+            format: OutputFormat::Text,
+            write: None,
+            method: mip.lp.method,
+            algorithm: mip.lp.algorithm,
+            init: mip.lp.init,
+            pivot_rule: None,
+            gap: mip.gap,
+            node_selection: mip.node_selection,
+            cut_rounds: mip.cut_rounds,
+            max_nodes: mip.max_nodes,
+            trace: None,
+        }
+    }
+}
+
+fn parse_gap(s: &str) -> anyhow::Result<f64> {
+    match s.parse::<f64>() {
+        Ok(gap) if gap >= 0.0 => Ok(gap),
+        _ => anyhow::bail!("invalid gap '{}', expected a non-negative number", s),
+    }
+}
+
+fn parse_count(s: &str) -> anyhow::Result<usize> {
+    s.parse::<usize>()
+        .map_err(|_| anyhow::anyhow!("invalid count '{}', expected a non-negative integer", s))
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> anyhow::Result<Args> {
+    let mut options = Options::default();
+    let mut stdin = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Args::Help),
+            "-i" | "--input" => match args.next() {
+                Some(f) => options.input_format = Some(f.parse()?),
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--input=") => {
+                options.input_format = Some(a["--input=".len()..].parse()?)
+            }
+            "-f" | "--format" => match args.next() {
+                Some(f) => options.format = f.parse()?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--format=") => options.format = a["--format=".len()..].parse()?,
+            "-w" | "--write" => match args.next() {
+                Some(f) => options.write = Some(f.parse()?),
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--write=") => options.write = Some(a["--write=".len()..].parse()?),
+            "--method" => match args.next() {
+                Some(m) => options.method = m.parse()?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--method=") => options.method = a["--method=".len()..].parse()?,
+            "--algorithm" => match args.next() {
+                Some(a) => options.algorithm = a.parse()?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--algorithm=") => {
+                options.algorithm = a["--algorithm=".len()..].parse()?
+            }
+            "--init" => match args.next() {
+                Some(i) => options.init = i.parse()?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--init=") => options.init = a["--init=".len()..].parse()?,
+            "--pivot-rule" => match args.next() {
+                Some(r) => options.pivot_rule = Some(r.parse()?),
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--pivot-rule=") => {
+                options.pivot_rule = Some(a["--pivot-rule=".len()..].parse()?)
+            }
+            "--gap" => match args.next() {
+                Some(g) => options.gap = parse_gap(&g)?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--gap=") => options.gap = parse_gap(&a["--gap=".len()..])?,
+            "--node-selection" => match args.next() {
+                Some(s) => options.node_selection = s.parse()?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--node-selection=") => {
+                options.node_selection = a["--node-selection=".len()..].parse()?
+            }
+            "--cut-rounds" => match args.next() {
+                Some(n) => options.cut_rounds = parse_count(&n)?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--cut-rounds=") => {
+                options.cut_rounds = parse_count(&a["--cut-rounds=".len()..])?
+            }
+            "--max-nodes" => match args.next() {
+                Some(n) => options.max_nodes = parse_count(&n)?,
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--max-nodes=") => {
+                options.max_nodes = parse_count(&a["--max-nodes=".len()..])?
+            }
+            "--trace" => match args.next() {
+                Some(f) => options.trace = Some(f.parse()?),
+                None => anyhow::bail!("missing value for '{}'", arg),
+            },
+            a if a.starts_with("--trace=") => options.trace = Some(a["--trace=".len()..].parse()?),
+            _ if stdin || options.input.is_some() => {
+                anyhow::bail!("more than one input file given")
+            }
+            "-" => stdin = true,
+            a if a.starts_with('-') => anyhow::bail!("unknown option '{}'", a),
+            _ => options.input = Some(arg),
+        }
+    }
+    if options.method == Method::Revised && options.algorithm == Algorithm::Dual {
+        anyhow::bail!("'--algorithm dual' needs the tableau, the revised simplex is primal only");
+    }
+    if options.method == Method::Revised && options.init != InitStrategy::TwoPhase {
+        anyhow::bail!("'--init' needs the tableau, the revised simplex has its own Phase I");
+    }
+    if options.method == Method::Revised && options.pivot_rule == Some(PivotRule::Lexicographic) {
+        anyhow::bail!("'--pivot-rule lexicographic' needs the tableau of the basis inverse");
+    }
+    if options.trace.is_some() {
+        if options.method == Method::Revised {
+            anyhow::bail!("'--trace' needs the tableau, the revised simplex has none");
+        }
+        if options.format != OutputFormat::Text {
+            anyhow::bail!("'--trace' only goes with text output");
+        }
+    }
+    Ok(Args::Solve(options))
+}
+
+fn exit_code(status: Status) -> i32 {
+    match status {
+        Status::Optimal => 0,
+        Status::Infeasible => 1,
+        Status::Unbounded => 2,
+        Status::IterationLimit => 3,
+    }
+}
+
+/// Write `text` to stdout in one go. A reader that went away, e.g. `head`,
+/// is not an error.
+fn print_out(text: &str) -> Result<(), Failure> {
+    let mut out = io::stdout().lock();
+    match out.write_all(text.as_bytes()).and_then(|_| out.flush()) {
+        Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(Failure::Output(err)),
+        _ => Ok(()),
+    }
+}
+
+/// Read the program and either solve it or print it. Returns the exit code.
+fn run<R: BufRead>(reader: R, options: &Options) -> Result<i32, Failure> {
+    let extension = options
+        .input
+        .as_ref()
+        .and_then(|path| Path::new(path).extension())
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    let input_format = options.input_format.unwrap_or(match extension.as_deref() {
+        Some("mps") => InputFormat::Mps(MpsFormat::Free),
+        Some("lp") => InputFormat::Cplex,
+        _ => InputFormat::Lp,
+    });
+    let symbols = match input_format {
+        InputFormat::Lp => construct_ast(reader).map(SymbolTable::from),
+        InputFormat::Mps(mps) => read_mps(reader, mps),
+        InputFormat::Cplex => read_lp(reader),
+    }
+    .map_err(Failure::Parse)?;
+
+    match options.write {
+        Some(ModelFormat::Mps) => {
+            let name = options
+                .input
+                .as_ref()
+                .and_then(|path| Path::new(path).file_stem())
+                .map_or(String::from("PROGRAM"), |s| s.to_string_lossy().to_string());
+            print_out(&write_mps(&symbols, &name).map_err(Failure::Model)?)?;
+            return Ok(0);
+        }
+        Some(ModelFormat::Cplex) => {
+            print_out(&write_lp(&symbols).map_err(Failure::Model)?)?;
+            return Ok(0);
+        }
+        None => {}
+    }
+
+    let mip = MipOptions {
+        gap: options.gap,
+        node_selection: options.node_selection,
+        cut_rounds: options.cut_rounds,
+        max_nodes: options.max_nodes,
+        lp: SolverOptions {
+            method: options.method,
+            algorithm: options.algorithm,
+            init: options.init,
+            // the revised simplex prices with Devex by default, which saves
+            // most of the iterations on large programs
+            pivot_rule: options.pivot_rule.unwrap_or(match options.method {
+                Method::Tableau => PivotRule::Dantzig,
+                Method::Revised => PivotRule::SteepestEdge,
+            }),
+            trace: options.trace.is_some(),
+            ..Default::default()
+        },
+    };
+    let solution = BranchAndBound::from_symbols(&symbols)
+        .map_err(Failure::Model)?
+        .solve_with(&mip)
+        .map_err(Failure::Solver)?;
+    let mut text = String::new();
+    if let (Some(format), Some(trace)) = (options.trace, &solution.trace) {
+        text.push_str(&trace.render(format));
+    }
+    text.push_str(&solution.render(options.format));
+    print_out(&text)?;
+    Ok(exit_code(solution.status))
+}
 
 fn main() {
-    println!("Hello, world!");
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Args::Help) => {
+            let _ = print_out(&format!("{}\n", USAGE));
+            return;
+        }
+        Ok(Args::Solve(options)) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(EXIT_USAGE);
+        }
+    };
+
+    let result = match &options.input {
+        Some(path) => File::open(path)
+            .map_err(|err| Failure::Parse(anyhow::anyhow!("cannot open '{}': {}", path, err)))
+            .and_then(|file| run(BufReader::new(file), &options)),
+        None => run(io::stdin().lock(), &options),
+    };
+    match result {
+        Ok(code) => process::exit(code),
+        Err(failure) => {
+            eprintln!("error: {}", failure);
+            process::exit(failure.exit_code());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        parse_args, Algorithm, Args, InitStrategy, InputFormat, Method, ModelFormat, MpsFormat,
+        NodeSelection, Options, OutputFormat, PivotRule, TraceFormat,
+    };
+
+    fn args(a: &[&str]) -> anyhow::Result<Args> {
+        parse_args(a.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(&[]).unwrap(), Args::Solve(Options::default()));
+        assert_eq!(args(&["-"]).unwrap(), Args::Solve(Options::default()));
+        assert_eq!(
+            args(&["lp.txt"]).unwrap(),
+            Args::Solve(Options {
+                input: Some(String::from("lp.txt")),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            args(&["--format", "json", "lp.txt"]).unwrap(),
+            Args::Solve(Options {
+                input: Some(String::from("lp.txt")),
+                format: OutputFormat::Json,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            args(&["-", "--format=csv"]).unwrap(),
+            Args::Solve(Options {
+                format: OutputFormat::Csv,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            args(&["-i", "fixed-mps", "--write", "cplex", "afiro.mps"]).unwrap(),
+            Args::Solve(Options {
+                input: Some(String::from("afiro.mps")),
+                input_format: Some(InputFormat::Mps(MpsFormat::Fixed)),
+                write: Some(ModelFormat::Cplex),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            args(&["--gap", "0.01", "--node-selection=depth-first"]).unwrap(),
+            Args::Solve(Options {
+                gap: 0.01,
+                node_selection: NodeSelection::DepthFirst,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            args(&["--cut-rounds", "0"]).unwrap(),
+            Args::Solve(Options {
+                cut_rounds: 0,
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--cut-rounds=x"]).is_err());
+        assert_eq!(
+            args(&["--max-nodes=50"]).unwrap(),
+            Args::Solve(Options {
+                max_nodes: 50,
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--max-nodes=x"]).is_err());
+        assert_eq!(
+            args(&["--algorithm=dual"]).unwrap(),
+            Args::Solve(Options {
+                algorithm: Algorithm::Dual,
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--algorithm", "barrier"]).is_err());
+        assert_eq!(
+            args(&["--method", "revised"]).unwrap(),
+            Args::Solve(Options {
+                method: Method::Revised,
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--method", "revised", "--algorithm", "dual"]).is_err());
+        assert_eq!(
+            args(&["--init", "big-m"]).unwrap(),
+            Args::Solve(Options {
+                init: InitStrategy::BigM(InitStrategy::DEFAULT_BIG_M),
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--init=two-phase"]).is_ok());
+        assert!(args(&["--init=big-m", "--method=revised"]).is_err());
+        assert!(args(&["--init", "phase-one"]).is_err());
+        assert_eq!(
+            args(&["--pivot-rule=bland", "--method", "revised"]).unwrap(),
+            Args::Solve(Options {
+                method: Method::Revised,
+                pivot_rule: Some(PivotRule::Bland),
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--pivot-rule", "lexicographic"]).is_ok());
+        assert!(args(&["--pivot-rule", "lexicographic", "--method=revised"]).is_err());
+        assert!(args(&["--pivot-rule", "random"]).is_err());
+        assert!(args(&["--method=sparse"]).is_err());
+        assert_eq!(
+            args(&["--trace", "markdown"]).unwrap(),
+            Args::Solve(Options {
+                trace: Some(TraceFormat::Markdown),
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--trace=html"]).is_err());
+        assert!(args(&["--trace"]).is_err());
+        assert!(args(&["--trace=text", "--method=revised"]).is_err());
+        assert!(args(&["--trace=latex", "--format=json"]).is_err());
+        assert!(args(&["--gap=-1"]).is_err());
+        assert!(args(&["--node-selection", "random"]).is_err());
+        assert!(args(&["--input=xml"]).is_err());
+        assert!(args(&["--write=xml"]).is_err());
+        assert!(args(&["--format", "xml"]).is_err());
+        assert!(args(&["--format"]).is_err());
+        assert_eq!(args(&["lp.txt", "--help"]).unwrap(), Args::Help);
+        assert!(args(&["--bogus"]).is_err());
+        assert!(args(&["a.txt", "b.txt"]).is_err());
+        assert!(args(&["-", "b.txt"]).is_err());
+    }
 }
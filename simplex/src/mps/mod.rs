@@ -0,0 +1,11 @@
+pub mod reader;
+pub mod writer;
+
+/// Layout of the data lines of an MPS file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpsFormat {
+    /// Fields at fixed character positions, names may contain spaces
+    Fixed,
+    /// Fields separated by whitespace
+    Free,
+}
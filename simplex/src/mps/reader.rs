@@ -0,0 +1,632 @@
+use std::{collections::HashMap, io::BufRead};
+
+use crate::{
+    lexer::tokens::{CmpOperation, F64},
+    mps::MpsFormat,
+    semantics::symbols::{Sense, Symbol, SymbolTable, RESERVED_NAMES},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Name,
+    ObjSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds,
+    End,
+}
+
+/// Row declared in the ROWS section
+#[derive(Debug, Clone, Copy)]
+enum Row {
+    Objective,
+    /// every `N` row after the first one, ignored
+    Free,
+    Constraint(u32),
+}
+
+/// Fields 1 to 6 of a data line, empty if missing: type code, name, row or
+/// column, value, row, value
+type Fields = [String; 6];
+
+/// Character spans of the fields in fixed MPS, the last field runs to the
+/// end of the line
+const FIXED_SPANS: [(usize, usize); 6] = [
+    (1, 3),
+    (4, 12),
+    (14, 22),
+    (24, 36),
+    (39, 47),
+    (49, usize::MAX),
+];
+
+fn fixed_fields(line: &str) -> Fields {
+    let chars = line.chars().collect::<Vec<_>>();
+    FIXED_SPANS.map(|(from, to)| {
+        let to = to.min(chars.len());
+        if from >= to {
+            String::new()
+        } else {
+            chars[from..to]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        }
+    })
+}
+
+/// Place whitespace separated tokens into the fields they occupy in fixed
+/// MPS. Set names are optional in free MPS, so their presence is derived
+/// from the number of tokens.
+fn free_fields(section: Section, line: &str) -> anyhow::Result<Fields> {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+    let positions: &[usize] = match section {
+        Section::Rows => &[0, 1],
+        Section::Columns => &[1, 2, 3, 4, 5],
+        Section::Rhs | Section::Ranges if tokens.len() % 2 == 1 => &[1, 2, 3, 4, 5],
+        Section::Rhs | Section::Ranges => &[2, 3, 4, 5],
+        Section::Bounds => {
+            let has_value = matches!(tokens[0], "UP" | "LO" | "FX" | "LI" | "UI" | "SC");
+            if tokens.len() == if has_value { 4 } else { 3 } {
+                &[0, 1, 2, 3]
+            } else {
+                &[0, 2, 3]
+            }
+        }
+        _ => &[],
+    };
+    if tokens.len() > positions.len() {
+        anyhow::bail!("too many fields");
+    }
+    let mut fields = Fields::default();
+    for (token, pos) in tokens.iter().zip(positions) {
+        fields[*pos] = token.to_string();
+    }
+    Ok(fields)
+}
+
+fn parse_value(s: &str) -> anyhow::Result<f64> {
+    s.parse::<f64>()
+        .map_err(|_| anyhow::anyhow!("'{}' is not a number", s))
+}
+
+/// Everything read so far, turned into a symbol table by `finish`
+#[derive(Debug, Default)]
+struct Model {
+    sense: Option<Sense>,
+    rows: HashMap<String, Row>,
+    obj_name: Option<String>,
+    /// constant of the objective, minus its right hand side
+    obj_constant: f64,
+    /// names of the constraint rows in order
+    row_names: Vec<String>,
+    cmp: Vec<CmpOperation>,
+    rhs: Vec<f64>,
+    ranges: Vec<Option<f64>>,
+    /// column names in order of appearance
+    columns: Vec<String>,
+    obj: HashMap<String, f64>,
+    coefs: HashMap<String, Vec<(u32, f64)>>,
+    lower: HashMap<String, f64>,
+    upper: HashMap<String, f64>,
+    /// between an `'INTORG'` and an `'INTEND'` marker
+    in_integer_marker: bool,
+    integers: Vec<String>,
+    /// only the first RHS, RANGES and BOUNDS set is used
+    rhs_set: Option<String>,
+    ranges_set: Option<String>,
+    bounds_set: Option<String>,
+}
+
+/// Whether a line belongs to the first set seen in its section
+fn in_set(set: &mut Option<String>, name: &str) -> bool {
+    set.get_or_insert_with(|| name.to_string()) == name
+}
+
+impl Model {
+    fn row(&self, name: &str) -> anyhow::Result<Row> {
+        self.rows
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("unknown row '{}'", name))
+    }
+
+    /// Row and value pairs in fields 3/4 and 5/6
+    fn pairs(&self, fields: &Fields) -> anyhow::Result<Vec<(Row, f64)>> {
+        let mut pairs = vec![];
+        for (row, value) in [(&fields[2], &fields[3]), (&fields[4], &fields[5])] {
+            if !row.is_empty() {
+                pairs.push((self.row(row)?, parse_value(value)?));
+            }
+        }
+        Ok(pairs)
+    }
+
+    fn read_row(&mut self, fields: &Fields) -> anyhow::Result<()> {
+        let name = fields[1].clone();
+        let row = match fields[0].as_str() {
+            "N" if self.rows.values().any(|r| matches!(r, Row::Objective)) => Row::Free,
+            "N" => {
+                self.obj_name = Some(name.clone());
+                Row::Objective
+            }
+            kind => {
+                self.cmp.push(match kind {
+                    "L" => CmpOperation::Leq,
+                    "G" => CmpOperation::Geq,
+                    "E" => CmpOperation::Eq,
+                    _ => anyhow::bail!("unknown row type '{}'", kind),
+                });
+                self.rhs.push(0.0);
+                self.ranges.push(None);
+                self.row_names.push(name.clone());
+                Row::Constraint(self.cmp.len() as u32 - 1)
+            }
+        };
+        if self.rows.insert(name.clone(), row).is_some() {
+            anyhow::bail!("row '{}' is declared twice", name);
+        }
+        Ok(())
+    }
+
+    fn read_column(&mut self, fields: &Fields) -> anyhow::Result<()> {
+        let name = &fields[1];
+        if RESERVED_NAMES.contains(&name.as_str()) {
+            anyhow::bail!("column name '{}' is reserved", name);
+        }
+        if !self.coefs.contains_key(name) {
+            self.columns.push(name.clone());
+            self.coefs.insert(name.clone(), vec![]);
+            if self.in_integer_marker {
+                self.integers.push(name.clone());
+            }
+        }
+        for (row, value) in self.pairs(fields)? {
+            match row {
+                Row::Objective => *self.obj.entry(name.clone()).or_default() += value,
+                Row::Free => {}
+                Row::Constraint(i) => self.coefs.get_mut(name).unwrap().push((i, value)),
+            }
+        }
+        Ok(())
+    }
+
+    /// `'MARKER'` line around integer columns
+    fn read_marker(&mut self, line: &str) -> anyhow::Result<()> {
+        if line.contains("'INTORG'") {
+            self.in_integer_marker = true;
+        } else if line.contains("'INTEND'") {
+            self.in_integer_marker = false;
+        } else {
+            anyhow::bail!("expected 'INTORG' or 'INTEND' after 'MARKER'");
+        }
+        Ok(())
+    }
+
+    fn read_rhs(&mut self, fields: &Fields) -> anyhow::Result<()> {
+        if !in_set(&mut self.rhs_set, &fields[1]) {
+            return Ok(());
+        }
+        // `obj = rhs` moves the right hand side of the objective over as
+        // its negated constant
+        for (row, value) in self.pairs(fields)? {
+            match row {
+                Row::Objective => self.obj_constant = -value,
+                Row::Free => {}
+                Row::Constraint(i) => self.rhs[i as usize] = value,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_range(&mut self, fields: &Fields) -> anyhow::Result<()> {
+        if !in_set(&mut self.ranges_set, &fields[1]) {
+            return Ok(());
+        }
+        for (row, value) in self.pairs(fields)? {
+            if let Row::Constraint(i) = row {
+                self.ranges[i as usize] = Some(value);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_bound(&mut self, fields: &Fields) -> anyhow::Result<()> {
+        if !in_set(&mut self.bounds_set, &fields[1]) {
+            return Ok(());
+        }
+        let column = fields[2].clone();
+        if !self.coefs.contains_key(&column) {
+            anyhow::bail!("unknown column '{}'", column);
+        }
+        let value = || parse_value(&fields[3]);
+        match fields[0].as_str() {
+            kind @ ("UP" | "UI") => {
+                if kind == "UI" {
+                    self.integers.push(column.clone());
+                }
+                let value = value()?;
+                // by convention a negative upper bound without a lower bound
+                // makes the variable unbounded below
+                if value < 0.0 && !self.lower.contains_key(&column) {
+                    self.lower.insert(column.clone(), f64::NEG_INFINITY);
+                }
+                self.upper.insert(column, value);
+            }
+            kind @ ("LO" | "LI") => {
+                if kind == "LI" {
+                    self.integers.push(column.clone());
+                }
+                self.lower.insert(column, value()?);
+            }
+            "BV" => {
+                self.integers.push(column.clone());
+                self.lower.insert(column.clone(), 0.0);
+                self.upper.insert(column, 1.0);
+            }
+            "FX" => {
+                let value = value()?;
+                self.lower.insert(column.clone(), value);
+                self.upper.insert(column, value);
+            }
+            "FR" => {
+                self.lower.insert(column.clone(), f64::NEG_INFINITY);
+                self.upper.insert(column, f64::INFINITY);
+            }
+            "MI" => {
+                self.lower.insert(column, f64::NEG_INFINITY);
+            }
+            "PL" => {
+                self.upper.insert(column, f64::INFINITY);
+            }
+            "SC" => anyhow::bail!("semi-continuous bound 'SC' is not supported"),
+            kind => anyhow::bail!("unknown bound type '{}'", kind),
+        }
+        Ok(())
+    }
+
+    /// Ranges become a second row with the same coefficients, appended after
+    /// the rows of the ROWS section
+    fn finish(self) -> anyhow::Result<SymbolTable> {
+        if self.obj_name.is_none() {
+            anyhow::bail!("no objective row, expected an 'N' row in the ROWS section");
+        }
+        let mut cmp = self.cmp;
+        let mut rhs = self.rhs;
+        let mut copies = HashMap::new();
+        for (i, range) in self.ranges.iter().enumerate() {
+            let Some(r) = *range else {
+                continue;
+            };
+            let (op, value) = match cmp[i] {
+                CmpOperation::Leq => (CmpOperation::Geq, rhs[i] - r.abs()),
+                CmpOperation::Geq => (CmpOperation::Leq, rhs[i] + r.abs()),
+                _ if r > 0.0 => {
+                    cmp[i] = CmpOperation::Geq;
+                    (CmpOperation::Leq, rhs[i] + r)
+                }
+                _ if r < 0.0 => {
+                    cmp[i] = CmpOperation::Leq;
+                    (CmpOperation::Geq, rhs[i] + r)
+                }
+                _ => continue,
+            };
+            copies.insert(i as u32, cmp.len() as u32);
+            cmp.push(op);
+            rhs.push(value);
+        }
+
+        let mut symbols = SymbolTable::new(self.sense.unwrap_or(Sense::Min));
+        symbols.n_constr = cmp.len() as u32;
+        symbols.obj_name = self.obj_name;
+        symbols.obj_constant = self.obj_constant;
+        for (row, name) in self.row_names.into_iter().enumerate() {
+            symbols.row_names.insert(row as u32, name);
+        }
+        for name in &self.columns {
+            symbols.table.insert(name.clone(), vec![]);
+            if let Some(c) = self.obj.get(name) {
+                symbols.insert(name, Symbol::Obj(F64(*c)));
+            }
+            for (row, value) in &self.coefs[name] {
+                symbols.insert(name, Symbol::Constr(*row, F64(*value)));
+                if let Some(copy) = copies.get(row) {
+                    symbols.insert(name, Symbol::Constr(*copy, F64(*value)));
+                }
+            }
+        }
+        for (row, (op, value)) in cmp.into_iter().zip(rhs).enumerate() {
+            symbols.insert("RHS", Symbol::RHS(row as u32, F64(value)));
+            symbols.insert("CMP", Symbol::Cmp(row as u32, op));
+        }
+        for (name, lower) in self.lower {
+            symbols.bounds_mut(&name).lower = lower;
+        }
+        for (name, upper) in self.upper {
+            symbols.bounds_mut(&name).upper = upper;
+        }
+        for name in &self.integers {
+            symbols.set_integer(name);
+        }
+        Ok(symbols)
+    }
+}
+
+/// Read a model in MPS format. The first `N` row is the objective, which is
+/// minimized unless an OBJSENSE section says otherwise.
+///
+/// Rows keep the order and names of the ROWS section (without `N` rows),
+/// followed by one unnamed row per range. Bounds are kept per column. A
+/// right hand side on the objective row is its negated constant. A file
+/// without a ROWS section, an objective row or the final ENDATA is rejected.
+/// Columns between `'INTORG'` and `'INTEND'` markers and columns with a
+/// `BV`, `LI` or `UI` bound are integer.
+pub fn read_mps<R: BufRead>(reader: R, format: MpsFormat) -> anyhow::Result<SymbolTable> {
+    let mut model = Model::default();
+    let mut section = None;
+    let mut has_rows = false;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.starts_with('*') {
+            continue;
+        }
+        let result = if !line.starts_with(char::is_whitespace) {
+            // section header, OBJSENSE may carry its value on the same line
+            let mut tokens = line.split_whitespace();
+            let next = match tokens.next().unwrap() {
+                "NAME" => Section::Name,
+                "OBJSENSE" => Section::ObjSense,
+                "ROWS" => Section::Rows,
+                "COLUMNS" => Section::Columns,
+                "RHS" => Section::Rhs,
+                "RANGES" => Section::Ranges,
+                "BOUNDS" => Section::Bounds,
+                "ENDATA" => Section::End,
+                header => Err(anyhow::anyhow!(
+                    "line {}: unknown section '{}'",
+                    i + 1,
+                    header
+                ))?,
+            };
+            section = Some(next);
+            has_rows |= next == Section::Rows;
+            match tokens.next() {
+                Some(sense) if next == Section::ObjSense => read_sense(&mut model, sense),
+                _ => Ok(()),
+            }
+        } else {
+            let Some(section) = section else {
+                anyhow::bail!("line {}: data before the first section", i + 1);
+            };
+            if section == Section::ObjSense {
+                read_sense(&mut model, line.trim())
+            } else if section == Section::Columns && line.contains("'MARKER'") {
+                model.read_marker(line)
+            } else {
+                let fields = match format {
+                    MpsFormat::Fixed => Ok(fixed_fields(line)),
+                    MpsFormat::Free => free_fields(section, line),
+                };
+                fields.and_then(|fields| match section {
+                    Section::Rows => model.read_row(&fields),
+                    Section::Columns => model.read_column(&fields),
+                    Section::Rhs => model.read_rhs(&fields),
+                    Section::Ranges => model.read_range(&fields),
+                    Section::Bounds => model.read_bound(&fields),
+                    _ => anyhow::bail!("unexpected data"),
+                })
+            }
+        };
+        result.map_err(|e| anyhow::anyhow!("line {}: {}", i + 1, e))?;
+        if section == Some(Section::End) {
+            break;
+        }
+    }
+    if !has_rows {
+        anyhow::bail!("missing ROWS section");
+    }
+    if section != Some(Section::End) {
+        anyhow::bail!("missing ENDATA at the end of the file");
+    }
+    model.finish()
+}
+
+fn read_sense(model: &mut Model, sense: &str) -> anyhow::Result<()> {
+    model.sense = Some(match sense {
+        "MAX" | "MAXIMIZE" => Sense::Max,
+        "MIN" | "MINIMIZE" => Sense::Min,
+        _ => anyhow::bail!("unknown objective sense '{}'", sense),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_mps;
+    use crate::{
+        mps::{writer::write_mps, MpsFormat},
+        semantics::{
+            branch_and_bound::BranchAndBound,
+            solution::Status,
+            symbols::{Bounds, Sense},
+        },
+    };
+
+    const TESTPROB: &str = "NAME          TESTPROB
+* the classic example, with YTWO >= 0 instead of YTWO >= -1
+ROWS
+ N  COST
+ L  LIM1
+ G  LIM2
+ E  MYEQN
+COLUMNS
+    XONE      COST                 1   LIM1                 1
+    XONE      LIM2                 1
+    YTWO      COST                 2   LIM1                 1
+    YTWO      MYEQN               -1
+    ZTHREE    COST                 3   LIM2                 1
+    ZTHREE    MYEQN                1
+RHS
+    RHS       LIM1                 4   LIM2                 1
+    RHS       MYEQN                7
+BOUNDS
+ UP BND       XONE                 4
+ UP BND       YTWO                 1
+ENDATA
+";
+
+    const RANGED: &str = "NAME ranged
+OBJSENSE
+    MAX
+ROWS
+ N obj
+ L c1
+ E c2
+ N unused
+COLUMNS
+ x obj 1 c1 1
+ x c2 1 unused 5
+ y obj 1 c1 1
+RHS
+ c1 10 c2 2
+RANGES
+ rng c1 4
+ rng c2 3
+BOUNDS
+ UP y 3
+ENDATA
+";
+
+    #[test]
+    fn test_read_fixed() {
+        let symbols = read_mps(TESTPROB.as_bytes(), MpsFormat::Fixed).unwrap();
+        assert_eq!(symbols.sense, Sense::Min);
+        assert_eq!(symbols.n_constr, 3);
+        assert_eq!(symbols.bounds("XONE").upper, 4.0);
+        assert_eq!(symbols.bounds("YTWO").upper, 1.0);
+        assert_eq!(symbols.bounds("ZTHREE"), Bounds::default());
+        assert_eq!(symbols.table.len(), 5);
+        assert_eq!(symbols.obj_name.as_deref(), Some("COST"));
+        assert_eq!(symbols.row_names[&2], "MYEQN");
+        assert!(!symbols.row_names.contains_key(&3));
+
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert!((sol.objective - 21.0).abs() < 1e-9);
+        assert!((sol.values["ZTHREE"] - 7.0).abs() < 1e-9);
+
+        // the same file is valid free MPS
+        let free = read_mps(TESTPROB.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(free.n_constr, 3);
+
+        let negative = TESTPROB.replace("ENDATA", " LO BND       YTWO                -1\nENDATA");
+        let symbols = read_mps(negative.as_bytes(), MpsFormat::Fixed).unwrap();
+        assert_eq!(symbols.bounds("YTWO").lower, -1.0);
+        // ZTHREE = 7 + YTWO, so YTWO drops to its new lower bound
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 16.0).abs() < 1e-9);
+        assert!((sol.values["YTWO"] + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_read_free() {
+        let symbols = read_mps(RANGED.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(symbols.sense, Sense::Max);
+        // c1, c2 and the range copies of both
+        assert_eq!(symbols.n_constr, 4);
+        assert_eq!(symbols.bounds("y").upper, 3.0);
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 8.0).abs() < 1e-9);
+
+        // a right hand side on the objective is its negated constant
+        let constant = RANGED.replace(" c1 10 c2 2", " c1 10 c2 2\n obj -2.5");
+        let symbols = read_mps(constant.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(symbols.obj_constant, 2.5);
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 10.5).abs() < 1e-9);
+        let mps = write_mps(&symbols, "constant").unwrap();
+        assert!(mps.contains("RHS\n    RHS obj -2.5\n"), "{}", mps);
+        let read = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(read.obj_constant, 2.5);
+
+        // the lower ends of the ranges: 6 <= x + y and 2 <= x
+        let min = RANGED.replace("MAX", "MIN");
+        let symbols = read_mps(min.as_bytes(), MpsFormat::Free).unwrap();
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 6.0).abs() < 1e-9);
+        assert!(sol.values["x"] >= 3.0 - 1e-9);
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let unknown_row = TESTPROB.replace(
+            "LIM2                 1\n    YTWO",
+            "LIM3                 1\n    YTWO",
+        );
+        let err = read_mps(unknown_row.as_bytes(), MpsFormat::Fixed).unwrap_err();
+        assert_eq!(err.to_string(), "line 10: unknown row 'LIM3'");
+
+        let marker = RANGED.replace(" y obj", " M 'MARKER' 'INTSTART'\n y obj");
+        assert!(read_mps(marker.as_bytes(), MpsFormat::Free).is_err());
+        let semi = RANGED.replace(" UP y 3", " SC y 3");
+        assert!(read_mps(semi.as_bytes(), MpsFormat::Free).is_err());
+
+        // not a model at all, or one cut short
+        let err = read_mps("".as_bytes(), MpsFormat::Free).unwrap_err();
+        assert_eq!(err.to_string(), "missing ROWS section");
+        let error_text = "error: 'x y' contains blanks\n";
+        assert!(read_mps(error_text.as_bytes(), MpsFormat::Free).is_err());
+        let truncated = RANGED.replace("ENDATA\n", "");
+        let err = read_mps(truncated.as_bytes(), MpsFormat::Free).unwrap_err();
+        assert_eq!(err.to_string(), "missing ENDATA at the end of the file");
+        let no_objective = RANGED
+            .replace(" N obj\n", "")
+            .replace(" N unused\n", "")
+            .replace(" unused 5", "")
+            .replace(" x obj 1 c1 1", " x c1 1")
+            .replace(" y obj 1 c1 1", " y c1 1");
+        let err = read_mps(no_objective.as_bytes(), MpsFormat::Free).unwrap_err();
+        assert!(err.to_string().contains("no objective row"), "{}", err);
+    }
+
+    #[test]
+    fn test_read_integers() {
+        let marker = RANGED
+            .replace(" x obj", " M1 'MARKER' 'INTORG'\n x obj")
+            .replace(" y obj", " M2 'MARKER' 'INTEND'\n y obj");
+        let symbols = read_mps(marker.as_bytes(), MpsFormat::Free).unwrap();
+        assert!(symbols.integers.contains("x"));
+        assert!(!symbols.integers.contains("y"));
+
+        let fixed = TESTPROB
+            .replace(" UP BND       XONE                 4", " BV BND       XONE")
+            .replace(
+                " UP BND       YTWO                 1",
+                " UI BND       YTWO                 1",
+            );
+        let symbols = read_mps(fixed.as_bytes(), MpsFormat::Fixed).unwrap();
+        assert_eq!(symbols.integers.len(), 2);
+        assert_eq!(symbols.bounds("XONE").upper, 1.0);
+        assert_eq!(symbols.bounds("YTWO").upper, 1.0);
+        assert!(!symbols.integers.contains("ZTHREE"));
+    }
+}
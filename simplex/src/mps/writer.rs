@@ -0,0 +1,247 @@
+use std::fmt::Write;
+
+use crate::{
+    lexer::tokens::CmpOperation,
+    semantics::{
+        program::LinearProgram,
+        symbols::{Sense, SymbolTable},
+    },
+};
+
+/// Write a program as free MPS with an explicit OBJSENSE, one coefficient
+/// per line and the bounds that differ from `[0, inf)`. Rows without a name
+/// are called `R<index>`, an unnamed objective `obj`. Variables without any
+/// coefficient get a zero objective entry so that they are not lost. An
+/// objective constant is written as the negated right hand side of the
+/// objective.
+/// Integer columns are enclosed in `'INTORG'`/`'INTEND'` markers.
+pub fn write_mps(symbols: &SymbolTable, name: &str) -> anyhow::Result<String> {
+    let program = LinearProgram::try_from(symbols)?;
+    let row_names = program.unique_row_names();
+    // the objective shares the namespace of the rows
+    let mut obj_row = program
+        .obj_name
+        .clone()
+        .unwrap_or_else(|| String::from("obj"));
+    while row_names.contains(&obj_row) {
+        obj_row.push('_');
+    }
+    let names = row_names.iter().chain(&program.names).chain([&obj_row]);
+    if let Some(name) = names.into_iter().find(|n| n.contains(char::is_whitespace)) {
+        anyhow::bail!(
+            "'{}' contains blanks, which free MPS cannot represent",
+            name
+        );
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "NAME {}", name);
+    let sense = match program.sense {
+        Sense::Max => "MAX",
+        Sense::Min => "MIN",
+    };
+    let _ = writeln!(out, "OBJSENSE\n    {}", sense);
+
+    let _ = writeln!(out, "ROWS\n N {}", obj_row);
+    for (row, cmp) in program.cmp.iter().enumerate() {
+        let kind = match cmp {
+            CmpOperation::Leq => "L",
+            CmpOperation::Geq => "G",
+            CmpOperation::Eq => "E",
+            _ => unreachable!("strict relations are rejected by LinearProgram"),
+        };
+        let _ = writeln!(out, " {} {}", kind, row_names[row]);
+    }
+
+    let _ = writeln!(out, "COLUMNS");
+    let mut markers = 0;
+    for (col, var) in program.names.iter().enumerate() {
+        if program.integer[col] != (markers % 2 == 1) {
+            let kind = if program.integer[col] {
+                "INTORG"
+            } else {
+                "INTEND"
+            };
+            let _ = writeln!(out, "    MARKER{} 'MARKER' '{}'", markers, kind);
+            markers += 1;
+        }
+        let entries = program
+            .matrix
+            .col(col)
+            .map(|(row, value)| (row_names[row].clone(), value));
+        let obj = program.obj[col];
+        let mut entries = (obj != 0.0)
+            .then(|| (obj_row.clone(), obj))
+            .into_iter()
+            .chain(entries)
+            .peekable();
+        if entries.peek().is_none() {
+            let _ = writeln!(out, "    {} {} 0", var, obj_row);
+        }
+        for (row, value) in entries {
+            let _ = writeln!(out, "    {} {} {}", var, row, value);
+        }
+    }
+
+    if markers % 2 == 1 {
+        let _ = writeln!(out, "    MARKER{} 'MARKER' 'INTEND'", markers);
+    }
+
+    let _ = writeln!(out, "RHS");
+    if program.obj_constant != 0.0 {
+        let _ = writeln!(out, "    RHS {} {}", obj_row, -program.obj_constant);
+    }
+    for (row, value) in program.rhs.iter().enumerate() {
+        if *value != 0.0 {
+            let _ = writeln!(out, "    RHS {} {}", row_names[row], value);
+        }
+    }
+
+    let mut bounds = String::new();
+    for (col, var) in program.names.iter().enumerate() {
+        let (lower, upper) = (program.lower[col], program.upper[col]);
+        if lower == upper {
+            let _ = writeln!(bounds, " FX BND {} {}", var, lower);
+            continue;
+        }
+        if lower == f64::NEG_INFINITY && upper == f64::INFINITY {
+            let _ = writeln!(bounds, " FR BND {}", var);
+            continue;
+        }
+        if lower == f64::NEG_INFINITY {
+            let _ = writeln!(bounds, " MI BND {}", var);
+        } else if lower != 0.0 || upper < 0.0 {
+            // a negative upper bound alone would also drop the lower bound
+            let _ = writeln!(bounds, " LO BND {} {}", var, lower);
+        }
+        if upper != f64::INFINITY {
+            let _ = writeln!(bounds, " UP BND {} {}", var, upper);
+        }
+    }
+    if !bounds.is_empty() {
+        let _ = write!(out, "BOUNDS\n{}", bounds);
+    }
+    let _ = writeln!(out, "ENDATA");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::write_mps;
+    use crate::{
+        mps::{reader::read_mps, MpsFormat},
+        parser::ast::construct_ast,
+        semantics::{branch_and_bound::BranchAndBound, symbols::SymbolTable},
+    };
+
+    #[test]
+    fn test_write_mps() {
+        let input = "
+min {2x1 + 3x2 + 0.5x3 }
+st {
+    x1 + x2 >= 4
+    x1 - x2 <= 1
+    x2 = 3
+}
+";
+        let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        let mps = write_mps(&symbols, "example").unwrap();
+        let truth = "NAME example
+OBJSENSE
+    MIN
+ROWS
+ N obj
+ G R0
+ L R1
+ E R2
+COLUMNS
+    x1 obj 2
+    x1 R0 1
+    x1 R1 1
+    x2 obj 3
+    x2 R0 1
+    x2 R1 -1
+    x2 R2 1
+    x3 obj 0.5
+RHS
+    RHS R0 4
+    RHS R1 1
+    RHS R2 3
+ENDATA
+";
+        assert_eq!(mps, truth);
+
+        // reading it back gives the same program
+        let read = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        let read_sol = BranchAndBound::from_symbols(&read)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 11.0).abs() < 1e-9);
+        assert!((read_sol.objective - sol.objective).abs() < 1e-9);
+        assert_eq!(read_sol.values, sol.values);
+
+        // names are kept, unnamed rows are numbered around them
+        let named = "
+max { obj: x1 }
+st {
+    R1: x1 <= 4
+    x1 <= 5
+}
+";
+        let symbols = SymbolTable::from(construct_ast(named.as_bytes()).unwrap());
+        let mps = write_mps(&symbols, "named").unwrap();
+        assert!(mps.contains("ROWS\n N obj\n L R1\n L R1_\n"), "{}", mps);
+        let read = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(read.obj_name.as_deref(), Some("obj"));
+        assert_eq!(read.row_names[&1], "R1_");
+
+        let bounded = format!(
+            "{}bounds {{ x1 free\n x2 <= -1\n 1 <= x3 <= 2\n x4 = 3\n x5 free; x5 <= 6 }}",
+            named
+        );
+        let symbols = SymbolTable::from(construct_ast(bounded.as_bytes()).unwrap());
+        let mps = write_mps(&symbols, "bounded").unwrap();
+        assert!(mps.ends_with(
+            "BOUNDS
+ FR BND x1
+ LO BND x2 0
+ UP BND x2 -1
+ LO BND x3 1
+ UP BND x3 2
+ FX BND x4 3
+ MI BND x5
+ UP BND x5 6
+ENDATA
+"
+        ));
+        let read = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        for var in ["x1", "x2", "x3", "x4", "x5"] {
+            assert_eq!(read.bounds(var), symbols.bounds(var));
+        }
+        // x2 has no value in [0, -1], which is written but not solved
+        let err = BranchAndBound::from_symbols(&read).err().unwrap();
+        assert!(err.to_string().contains("'x2'"), "{}", err);
+
+        let integer = format!("{}int {{ x2 }}\nbin {{ x3 }}", input);
+        let symbols = SymbolTable::from(construct_ast(integer.as_bytes()).unwrap());
+        let mps = write_mps(&symbols, "integer").unwrap();
+        assert!(mps.contains(
+            "    x1 R1 1
+    MARKER0 'MARKER' 'INTORG'
+    x2 obj 3"
+        ));
+        assert!(mps.contains(
+            "    x3 obj 0.5
+    MARKER1 'MARKER' 'INTEND'
+RHS"
+        ));
+        let read = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        assert_eq!(read.integers, symbols.integers);
+        assert_eq!(read.bounds("x3").upper, 1.0);
+    }
+}
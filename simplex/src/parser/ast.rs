@@ -14,12 +14,15 @@ pub enum ASTNodeTypes {
     Constraint,
     Expression,
     Term,
+    Label,
+    Bounds,
+    Bound,
+    Integers,
 }
 
 #[derive(Debug)]
 pub struct ASTNode {
     pub node_type: ASTNodeTypes,
-    pub parent: Option<usize>,
     pub children: Option<Vec<usize>>,
     pub data_index: Option<usize>,
 }
@@ -27,13 +30,11 @@ pub struct ASTNode {
 impl ASTNode {
     pub fn new(
         node_type: ASTNodeTypes,
-        parent: Option<usize>,
         children: Option<Vec<usize>>,
         data_index: Option<usize>,
     ) -> Self {
         Self {
             node_type,
-            parent,
             children,
             data_index,
         }
@@ -60,7 +61,7 @@ impl AST {
             self.data.push(t);
             data_index = Some(self.data.len() - 1);
         }
-        let node = ASTNode::new(node_type, parent, None, data_index);
+        let node = ASTNode::new(node_type, None, data_index);
         self.nodes.push(node);
         let id = self.nodes.len() - 1;
         if let Some(pidx) = parent {
@@ -83,20 +84,6 @@ impl AST {
         None
     }
 
-    pub fn print_subtree(&self, root: usize, level: usize) {
-        let node = &self.nodes[root];
-        let indent = "-".repeat(level * 2);
-        if let Some(token_idx) = node.data_index {
-            println!("{} {:?}", indent, &self.data[token_idx]);
-        }
-
-        if let Some(children) = &node.children {
-            for child in children {
-                self.print_subtree(*child, level + 1);
-            }
-        }
-    }
-
     pub fn new() -> Self {
         Self {
             data: Vec::new(),
@@ -108,7 +95,11 @@ impl AST {
 pub fn construct_ast<R: BufRead>(reader: R) -> anyhow::Result<AST> {
     let mut iterator = Tokenizer::new(reader).into_iter();
     let mut ast = AST::new();
-    program(&mut iterator, &mut ast)?;
+    let result = program(&mut iterator, &mut ast);
+    if let Some(err) = iterator.error() {
+        anyhow::bail!("{}", err);
+    }
+    result?;
     Ok(ast)
 }
 
@@ -129,6 +120,85 @@ st {
             // println!("{:#?}", ast);
             assert!(ast.is_ok());
         }
+        {
+            // `;` ends a statement like a line break
+            let input = "max { x1 + x2; }\nst { x1 <= 4; x2 <= 2; }\n";
+            assert!(construct_ast(input.as_bytes()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_ast_comments() {
+        let input = "
+// production plan
+max {3x1 + 5x2 } # profit per unit
+
+/* capacities of
+   the three plants */
+st {
+    x1 <= 4 // plant 1
+    2x2 <= 12 /* plant 2 */
+    # plant 3
+    3x1 + 2x2 <= 18
+}
+";
+        assert!(construct_ast(input.as_bytes()).is_ok());
+
+        let err = construct_ast("max {x}\nst {\nx <= 4 /* open\n}".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "unterminated block comment from line 3");
+        let open = input.replace("*/", "");
+        assert!(construct_ast(open.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ast_bounds() {
+        let input = "
+max {x_1 + x_2 + y }
+st {
+    x_1 + x_2 <= 50
+}
+bounds { 0 <= x_1 <= 40; x_2 free; y >= -5 }
+";
+        assert!(construct_ast(input.as_bytes()).is_ok());
+        let multi_line = input.replace("; ", "\n").replace("{ 0", "{\n0");
+        assert!(construct_ast(multi_line.as_bytes()).is_ok());
+
+        for bound in [
+            "2x_1 <= 4",
+            "0 <= x_1 >= 4",
+            "x_1 < 4",
+            "0 <= x_1 free",
+            "x_1",
+        ] {
+            let input = input.replace("0 <= x_1 <= 40", bound);
+            assert!(construct_ast(input.as_bytes()).is_err(), "{}", bound);
+        }
+    }
+
+    #[test]
+    fn test_ast_integers() {
+        let input = "
+max {x_1 + x_2 + y }
+st {
+    x_1 + x_2 + y <= 50
+}
+bounds { x_1 <= 40 }
+int { x_1, x_2 }
+bin {
+    y
+}
+";
+        assert!(construct_ast(input.as_bytes()).is_ok());
+        // without bounds and with a line per name
+        let input = input
+            .replace("bounds { x_1 <= 40 }\n", "")
+            .replace(", ", "\n");
+        assert!(construct_ast(input.as_bytes()).is_ok());
+
+        for section in ["int { 2x_1 }", "int { x_1 <= 4 }", "int x_1"] {
+            let input = input.replace("bin {", &format!("{}\nbin {{", section));
+            assert!(construct_ast(input.as_bytes()).is_err(), "{}", section);
+        }
     }
 
     // TODO: Look at this case fix it
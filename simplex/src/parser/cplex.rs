@@ -0,0 +1,609 @@
+use std::{collections::HashMap, fmt::Write, io::BufRead};
+
+use crate::{
+    lexer::{
+        cplex::{is_valid_name, tokenize_lp, LpToken},
+        tokens::{ArithOperation, CmpOperation, F64},
+    },
+    semantics::{
+        program::LinearProgram,
+        symbols::{Sense, Symbol, SymbolTable},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Section {
+    Objective,
+    Constraints,
+    Bounds,
+    Generals,
+    Binaries,
+    End,
+}
+
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    (&s[..end], &s[end..])
+}
+
+/// Section keyword at the start of a line and the rest of the line.
+/// Keywords are reserved, a line starting with a variable named like one is
+/// read as a section header.
+fn header(line: &str) -> Option<(Section, Option<Sense>, &str)> {
+    let (first, rest) = split_word(line);
+    let first = first.to_lowercase();
+    let (section, sense) = match first.as_str() {
+        "maximize" | "maximise" | "maximum" | "max" => (Section::Objective, Some(Sense::Max)),
+        "minimize" | "minimise" | "minimum" | "min" => (Section::Objective, Some(Sense::Min)),
+        "subject" | "such" => {
+            let (second, rest) = split_word(rest);
+            let second = second.to_lowercase();
+            return match (first.as_str(), second.as_str()) {
+                ("subject", "to") | ("such", "that") => Some((Section::Constraints, None, rest)),
+                _ => None,
+            };
+        }
+        "st" | "s.t." | "st." => (Section::Constraints, None),
+        "bounds" | "bound" => (Section::Bounds, None),
+        "generals" | "general" | "gen" | "integers" => (Section::Generals, None),
+        "binaries" | "binary" | "bin" => (Section::Binaries, None),
+        "end" => (Section::End, None),
+        _ => return None,
+    };
+    Some((section, sense, rest))
+}
+
+fn is_infinity(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "inf" | "infinity")
+}
+
+/// Name the reader would take for a section header, infinity or `free`
+fn is_keyword(name: &str) -> bool {
+    header(name).is_some() || is_infinity(name) || name.eq_ignore_ascii_case("free")
+}
+
+/// Tokens of one section, each with the line it was read from
+struct LpParser {
+    tokens: Vec<(usize, LpToken)>,
+    pos: usize,
+}
+
+impl LpParser {
+    fn peek(&self) -> Option<&LpToken> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&LpToken> {
+        self.tokens.get(self.pos + offset).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<LpToken> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    /// Prefix errors with the line of the current token
+    fn error(&self, msg: &str) -> anyhow::Error {
+        match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some((line, _)) => anyhow::anyhow!("line {}: {}", line, msg),
+            None => anyhow::anyhow!("{}", msg),
+        }
+    }
+
+    /// Optional `name:` in front of the objective or a constraint
+    fn label(&mut self) -> Option<String> {
+        if let (Some(LpToken::Name(name)), Some(LpToken::Colon)) = (self.peek(), self.peek_at(1)) {
+            let name = name.clone();
+            self.pos += 2;
+            return Some(name);
+        }
+        None
+    }
+
+    fn sign(&mut self) -> f64 {
+        let mut sign = 1.0;
+        while let Some(LpToken::Op(op)) = self.peek() {
+            if *op == ArithOperation::Sub {
+                sign = -sign;
+            }
+            self.pos += 1;
+        }
+        sign
+    }
+
+    /// Linear expression `[+-] [coef] name {+- [coef] name}`, may be empty
+    fn expression(&mut self) -> anyhow::Result<Vec<(String, f64)>> {
+        let mut terms = vec![];
+        loop {
+            let start = self.pos;
+            let sign = self.sign();
+            let coef = match self.peek() {
+                Some(LpToken::Num(n)) => {
+                    let n = *n;
+                    self.pos += 1;
+                    Some(n)
+                }
+                _ => None,
+            };
+            let name = match (self.peek(), self.peek_at(1)) {
+                // the label of the next constraint
+                (Some(LpToken::Name(_)), Some(LpToken::Colon)) if coef.is_none() => None,
+                (Some(LpToken::Name(name)), _) => Some(name.clone()),
+                _ => None,
+            };
+            let Some(name) = name else {
+                if self.pos != start || !terms.is_empty() {
+                    return Err(self.error("expected a variable, constant terms are not supported"));
+                }
+                return Ok(terms);
+            };
+            self.pos += 1;
+            terms.push((name, sign * coef.unwrap_or(1.0)));
+            if !matches!(self.peek(), Some(LpToken::Op(_))) {
+                return Ok(terms);
+            }
+        }
+    }
+
+    /// Signed number or infinity
+    fn value(&mut self) -> anyhow::Result<f64> {
+        let sign = self.sign();
+        match self.next() {
+            Some(LpToken::Num(n)) => Ok(sign * n),
+            Some(LpToken::Name(name)) if is_infinity(&name) => Ok(sign * f64::INFINITY),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a number"))
+            }
+        }
+    }
+
+    fn cmp(&mut self) -> anyhow::Result<CmpOperation> {
+        match self.next() {
+            Some(LpToken::Cmp(op)) => Ok(op),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected one of <=, >= or ="))
+            }
+        }
+    }
+
+    /// One bound: `x free`, `x (cmp) v`, `v (cmp) x` or `v (cmp) x (cmp) w`.
+    /// Returns the variable and which of its bounds to set.
+    fn bound(&mut self) -> anyhow::Result<(String, Vec<(CmpOperation, f64)>)> {
+        if let Some(LpToken::Name(name)) = self.peek() {
+            if !is_infinity(name) {
+                let name = name.clone();
+                self.pos += 1;
+                if let Some(LpToken::Name(free)) = self.peek() {
+                    if free.eq_ignore_ascii_case("free") {
+                        self.pos += 1;
+                        return Ok((
+                            name,
+                            vec![
+                                (CmpOperation::Geq, f64::NEG_INFINITY),
+                                (CmpOperation::Leq, f64::INFINITY),
+                            ],
+                        ));
+                    }
+                }
+                let op = self.cmp()?;
+                return Ok((name, vec![(op, self.value()?)]));
+            }
+        }
+
+        let value = self.value()?;
+        // `v <= x` is `x >= v`
+        let op = self.cmp()?.reversed();
+        let name = match self.next() {
+            Some(LpToken::Name(name)) => name,
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a variable"));
+            }
+        };
+        let mut bounds = vec![(op, value)];
+        if let Some(LpToken::Cmp(_)) = self.peek() {
+            let op = self.cmp()?;
+            bounds.push((op, self.value()?));
+        }
+        Ok((name, bounds))
+    }
+}
+
+/// Read a program in CPLEX LP format. Rows are numbered in the order of the
+/// `Subject To` section, followed by the rows that bounds translate to.
+pub fn read_lp<R: BufRead>(reader: R) -> anyhow::Result<SymbolTable> {
+    let mut sense = None;
+    let mut sections: HashMap<Section, Vec<(usize, LpToken)>> = HashMap::new();
+    let mut section = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let rest = match header(&line) {
+            Some((next, next_sense, rest)) => {
+                if next == Section::Objective {
+                    if sense.is_some() {
+                        anyhow::bail!("line {}: more than one objective", i + 1);
+                    }
+                    sense = next_sense;
+                }
+                section = Some(next);
+                rest
+            }
+            None => line.as_str(),
+        };
+        if section == Some(Section::End) {
+            break;
+        }
+        let tokens = tokenize_lp(rest).map_err(|e| anyhow::anyhow!("line {}: {}", i + 1, e))?;
+        if tokens.is_empty() {
+            continue;
+        }
+        let Some(section) = section else {
+            anyhow::bail!("line {}: expected Maximize or Minimize", i + 1);
+        };
+        let entry = sections.entry(section).or_default();
+        entry.extend(tokens.into_iter().map(|t| (i + 1, t)));
+    }
+    let Some(sense) = sense else {
+        anyhow::bail!("missing Maximize or Minimize section");
+    };
+    let mut parser = |section| LpParser {
+        tokens: sections.remove(&section).unwrap_or_default(),
+        pos: 0,
+    };
+
+    let mut symbols = SymbolTable::new(sense);
+    let mut objective = parser(Section::Objective);
+    symbols.obj_name = objective.label();
+    for (name, value) in objective.expression()? {
+        symbols.insert(&name, Symbol::Obj(F64(value)));
+    }
+    if !objective.done() {
+        return Err(objective.error("unexpected token in the objective"));
+    }
+
+    let mut constraints = parser(Section::Constraints);
+    while !constraints.done() {
+        let name = constraints.label();
+        let expr = constraints.expression()?;
+        if expr.is_empty() {
+            return Err(constraints.error("expected a variable"));
+        }
+        let op = constraints.cmp()?;
+        let rhs = constraints.value()?;
+        symbols.push_row(name, &expr, op, rhs);
+    }
+
+    let mut bounds = parser(Section::Bounds);
+    while !bounds.done() {
+        let (name, set) = bounds.bound()?;
+        let range = symbols.bounds_mut(&name);
+        for (op, value) in set {
+            range.restrict(&op, value);
+        }
+    }
+
+    for section in [Section::Generals, Section::Binaries] {
+        let mut integers = parser(section);
+        while !integers.done() {
+            let Some(LpToken::Name(name)) = integers.peek().cloned() else {
+                return Err(integers.error("expected a variable"));
+            };
+            integers.next();
+            if section == Section::Binaries {
+                symbols.set_binary(&name);
+            } else {
+                symbols.set_integer(&name);
+            }
+        }
+    }
+    Ok(symbols)
+}
+
+/// `3 x + y - 2 z`, wrapped every few terms. An empty sum is written as
+/// `0 fallback` since CPLEX LP has no empty rows.
+fn write_terms(out: &mut String, terms: &[(&String, f64)], fallback: Option<&String>) {
+    const TERMS_PER_LINE: usize = 8;
+    if terms.is_empty() {
+        if let Some(name) = fallback {
+            let _ = write!(out, " 0 {}", name);
+        }
+        return;
+    }
+    for (i, (name, value)) in terms.iter().enumerate() {
+        if i > 0 && i % TERMS_PER_LINE == 0 {
+            out.push_str("\n   ");
+        }
+        let sign = if *value < 0.0 { "-" } else { "+" };
+        if i > 0 || *value < 0.0 {
+            let _ = write!(out, " {}", sign);
+        }
+        if value.abs() != 1.0 {
+            let _ = write!(out, " {}", value.abs());
+        }
+        let _ = write!(out, " {}", name);
+    }
+}
+
+/// Write a program in CPLEX LP format, rows without a name are called
+/// `R<index>` and an unnamed objective `obj`. Variables without any
+/// coefficient are listed in `Bounds`, even with default bounds, so that
+/// they are not lost. Integer variables, binaries included, go to `Generals`.
+/// An objective constant, as read from MPS, can not be written, nor can
+/// names that are keywords of the format, like `end` or `inf`.
+pub fn write_lp(symbols: &SymbolTable) -> anyhow::Result<String> {
+    let program = LinearProgram::try_from(symbols)?;
+    let row_names = program.unique_row_names();
+    let obj_name = program
+        .obj_name
+        .clone()
+        .unwrap_or_else(|| String::from("obj"));
+    if program.obj_constant != 0.0 {
+        anyhow::bail!(
+            "the objective constant {} can not be written, CPLEX LP has no constant terms",
+            program.obj_constant
+        );
+    }
+    let names = program.names.iter().chain(&row_names).chain([&obj_name]);
+    for name in names {
+        if !is_valid_name(name) {
+            anyhow::bail!("'{}' is not a valid CPLEX LP name", name);
+        }
+        if is_keyword(name) {
+            anyhow::bail!("'{}' is a CPLEX LP keyword and can not be a name", name);
+        }
+    }
+    let nonzero = |values: &[f64]| {
+        program
+            .names
+            .iter()
+            .zip(values.iter().copied())
+            .filter(|(_, v)| *v != 0.0)
+            .collect::<Vec<_>>()
+    };
+
+    let mut out = String::new();
+    out.push_str(match program.sense {
+        Sense::Max => "Maximize\n",
+        Sense::Min => "Minimize\n",
+    });
+    let _ = write!(out, " {}:", obj_name);
+    write_terms(&mut out, &nonzero(&program.obj), None);
+    out.push_str("\nSubject To\n");
+    for (row, name) in row_names.iter().enumerate() {
+        let _ = write!(out, " {}:", name);
+        let coefs = program
+            .matrix
+            .row(row)
+            .map(|(j, value)| (&program.names[j], value))
+            .collect::<Vec<_>>();
+        write_terms(&mut out, &coefs, program.names.first());
+        let cmp = match program.cmp[row] {
+            CmpOperation::Leq => "<=",
+            CmpOperation::Geq => ">=",
+            _ => "=",
+        };
+        let _ = writeln!(out, " {} {}", cmp, program.rhs[row]);
+    }
+
+    let bounded = program
+        .names
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            let unused = program.obj[*i] == 0.0 && program.matrix.col(*i).next().is_none();
+            unused || program.lower[*i] != 0.0 || program.upper[*i] != f64::INFINITY
+        })
+        .collect::<Vec<_>>();
+    if !bounded.is_empty() {
+        out.push_str("Bounds\n");
+        for (i, name) in bounded {
+            let (lower, upper) = (program.lower[i], program.upper[i]);
+            let _ = match (lower.is_finite(), upper.is_finite()) {
+                _ if lower == upper => writeln!(out, " {} = {}", name, lower),
+                (false, false) => writeln!(out, " {} free", name),
+                (false, true) => writeln!(out, " -inf <= {} <= {}", name, upper),
+                (true, false) => writeln!(out, " {} <= {}", lower, name),
+                (true, true) => writeln!(out, " {} <= {} <= {}", lower, name, upper),
+            };
+        }
+    }
+    if program.integer.contains(&true) {
+        out.push_str("Generals\n");
+        for (name, _) in program
+            .names
+            .iter()
+            .zip(&program.integer)
+            .filter(|(_, i)| **i)
+        {
+            let _ = writeln!(out, " {}", name);
+        }
+    }
+    out.push_str("End\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_lp, write_lp};
+    use crate::{
+        lexer::tokens::CmpOperation,
+        mps::{reader::read_mps, MpsFormat},
+        parser::ast::construct_ast,
+        semantics::{
+            branch_and_bound::BranchAndBound,
+            program::LinearProgram,
+            symbols::{Bounds, Sense, SymbolTable},
+        },
+        test_util::dense_row,
+    };
+
+    #[test]
+    fn test_read_lp() {
+        let input = "\\ Wyndor Glass
+Maximize
+ profit: 3 x1 + 5 x2
+Subject To
+ plant1: x1 <= 4
+ plant2: 2 x2
+   <= 12
+ plant3: 3 x1 + 2 x2 =< 18
+ -x1 - x2 >= -100
+Bounds
+ x2 <= 10
+ 1 <= x1 <= 3
+ 0 <= unused
+Generals
+End
+";
+        let symbols = read_lp(input.as_bytes()).unwrap();
+        assert_eq!(symbols.sense, Sense::Max);
+        assert_eq!(symbols.n_constr, 4);
+        assert_eq!(
+            symbols.bounds("x1"),
+            Bounds {
+                lower: 1.0,
+                upper: 3.0
+            }
+        );
+        assert_eq!(symbols.bounds("x2").upper, 10.0);
+        assert_eq!(symbols.bounds("unused"), Bounds::default());
+        let program = LinearProgram::try_from(&symbols).unwrap();
+        assert_eq!(program.names, vec!["unused", "x1", "x2"]);
+        assert_eq!(dense_row(&program.matrix, 3), vec![0.0, -1.0, -1.0]);
+        assert_eq!(program.rhs[3], -100.0);
+        assert_eq!(program.cmp[2], CmpOperation::Leq);
+
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 36.0).abs() < 1e-9);
+
+        let free = input.replace("0 <= unused", "x1 free");
+        let symbols = read_lp(free.as_bytes()).unwrap();
+        assert_eq!(symbols.bounds("x1"), Bounds::FREE);
+        let sol = BranchAndBound::from_symbols(&symbols)
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert!((sol.objective - 36.0).abs() < 1e-9);
+        let integer = input.replace("Generals", "Generals\n x1\nBinaries\n b");
+        let symbols = read_lp(integer.as_bytes()).unwrap();
+        assert!(symbols.integers.contains("x1") && symbols.integers.contains("b"));
+        assert_eq!(symbols.bounds("b").upper, 1.0);
+        let err = read_lp(input.replace("Generals", "Generals\n 3").as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 15: expected a variable");
+        let constant = input.replace("3 x1 + 5 x2", "3 x1 + 5");
+        assert!(read_lp(constant.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_write_lp() {
+        let input = "
+min {2x1 - x2 + 0x3 }
+st {
+    x1 + x2 >= 4
+    x1 - 1.5x2 <= 1
+}
+";
+        let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        let lp = write_lp(&symbols).unwrap();
+        let truth = "Minimize
+ obj: 2 x1 - x2
+Subject To
+ R0: x1 + x2 >= 4
+ R1: x1 - 1.5 x2 <= 1
+Bounds
+ 0 <= x3
+End
+";
+        assert_eq!(lp, truth);
+
+        let read = read_lp(lp.as_bytes()).unwrap();
+        let read_program = LinearProgram::try_from(&read).unwrap();
+        let program = LinearProgram::try_from(&symbols).unwrap();
+        assert_eq!(read_program.names, program.names);
+        assert_eq!(read_program.matrix, program.matrix);
+        assert_eq!(read_program.obj, program.obj);
+
+        let named = input
+            .replace("min {", "min { cost:")
+            .replace("    x1 + x2", "    demand: x1 + x2");
+        let symbols = SymbolTable::from(construct_ast(named.as_bytes()).unwrap());
+        let lp = write_lp(&symbols).unwrap();
+        assert!(lp.starts_with("Minimize\n cost: 2 x1 - x2\nSubject To\n demand: x1"));
+        let read = read_lp(lp.as_bytes()).unwrap();
+        assert_eq!(read.obj_name.as_deref(), Some("cost"));
+        assert_eq!(read.row_names[&0], "demand");
+
+        let bounded = format!("{}bounds {{ x1 free; x2 <= -1; 1 <= x3 <= 2 }}", input);
+        let symbols = SymbolTable::from(construct_ast(bounded.as_bytes()).unwrap());
+        let lp = write_lp(&symbols).unwrap();
+        assert!(lp.ends_with("Bounds\n x1 free\n 0 <= x2 <= -1\n 1 <= x3 <= 2\nEnd\n"));
+        let read = read_lp(lp.as_bytes()).unwrap();
+        for var in ["x1", "x2", "x3"] {
+            assert_eq!(read.bounds(var), symbols.bounds(var));
+        }
+
+        let integer = format!("{}int {{ x1 }}\nbin {{ x3 }}", input);
+        let symbols = SymbolTable::from(construct_ast(integer.as_bytes()).unwrap());
+        let lp = write_lp(&symbols).unwrap();
+        assert!(lp.ends_with("Bounds\n 0 <= x3 <= 1\nGenerals\n x1\n x3\nEnd\n"));
+        let read = read_lp(lp.as_bytes()).unwrap();
+        assert_eq!(read.integers, symbols.integers);
+        assert_eq!(read.bounds("x3"), symbols.bounds("x3"));
+
+        // names that only start like a keyword are fine
+        let near = "
+max { ends + bins + infx + freed }
+st {
+    st_1: ends + bins + infx + freed <= 4
+}
+bounds { ends free; bins <= 1; 1 <= infx <= 2; freed = 1 }
+int { bins }
+";
+        let symbols = SymbolTable::from(construct_ast(near.as_bytes()).unwrap());
+        let lp = write_lp(&symbols).unwrap();
+        let read = read_lp(lp.as_bytes()).unwrap();
+        assert_eq!(read.integers, symbols.integers);
+        assert_eq!(read.row_names[&0], "st_1");
+        for var in ["ends", "bins", "infx", "freed"] {
+            assert_eq!(read.bounds(var), symbols.bounds(var), "{}", var);
+        }
+        let program = LinearProgram::try_from(&symbols).unwrap();
+        let read_program = LinearProgram::try_from(&read).unwrap();
+        assert_eq!(read_program.names, program.names);
+        assert_eq!(read_program.matrix, program.matrix);
+
+        for keyword in ["end", "Bin", "inf", "Generals", "Minimize"] {
+            let input = near.replace("ends", keyword);
+            let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+            let err = write_lp(&symbols).unwrap_err();
+            assert!(err.to_string().contains("keyword"), "{}", keyword);
+        }
+        let row = near.replace("st_1:", "end:");
+        let symbols = SymbolTable::from(construct_ast(row.as_bytes()).unwrap());
+        assert!(write_lp(&symbols).is_err());
+        // the lp syntax reserves `free` itself, MPS does not, nor does it
+        // reserve constant terms
+        let mps =
+            "NAME T\nROWS\n N obj\n L c1\nCOLUMNS\n free obj 1 c1 1\nRHS\n RHS c1 4\nENDATA\n";
+        let symbols = read_mps(mps.as_bytes(), MpsFormat::Free).unwrap();
+        let err = write_lp(&symbols).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'free' is a CPLEX LP keyword and can not be a name"
+        );
+        let constant = mps.replace("free", "x").replace("c1 4", "c1 4 obj 1");
+        let symbols = read_mps(constant.as_bytes(), MpsFormat::Free).unwrap();
+        assert!(write_lp(&symbols).is_err());
+    }
+}
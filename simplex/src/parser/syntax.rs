@@ -2,7 +2,7 @@ use std::io::BufRead;
 
 use crate::lexer::{
     tokenizer::TokenizerIterator,
-    tokens::{ArithOperation, Token, F64},
+    tokens::{ArithOperation, CmpOperation, Token, F64},
 };
 
 use super::ast::{ASTNodeTypes, AST};
@@ -52,6 +52,7 @@ fn pull_and_compare_token<R: BufRead>(
     }
 }
 
+#[allow(clippy::only_used_in_recursion)]
 pub fn ignore_eol<R: BufRead>(
     iterator: &mut TokenizerIterator<R>,
     ast: &mut AST,
@@ -75,13 +76,15 @@ pub fn program<R: BufRead>(
 ) -> anyhow::Result<()> {
     match iterator.peek() {
         Some(Token::Fun(s)) => {
-            if s == String::from("max") || s == String::from("min") {
+            if s == "max" || s == "min" {
                 let program_node = ast.insert_node(None, None, ASTNodeTypes::Program);
                 objective(iterator, ast, program_node)?;
                 // check EOL, but dont use it
                 let _ = pull_and_compare_content(iterator, &[Token::EOL], "expected line break")?;
                 ignore_eol(iterator, ast, program_node)?;
                 constraints(iterator, ast, program_node)?;
+                bounds(iterator, ast, program_node)?;
+                integers(iterator, ast, program_node)?;
                 Ok(())
             } else {
                 iterator.raise_parsing_error("expected objective declaration");
@@ -96,6 +99,8 @@ pub fn program<R: BufRead>(
             let _ = pull_and_compare_content(iterator, &[Token::EOL], "expected line break")?;
             ignore_eol(iterator, ast, program_node)?;
             constraints(iterator, ast, program_node)?;
+            bounds(iterator, ast, program_node)?;
+            integers(iterator, ast, program_node)?;
             Ok(())
         }
         Some(_) | None => {
@@ -125,6 +130,7 @@ pub fn objective<R: BufRead>(
     );
     pull_and_compare_content(iterator, &[Token::LParen('{')], "expected '{'")?;
     ignore_eol(iterator, ast, objective_node)?;
+    label(iterator, ast, objective_node)?;
     expression(iterator, ast, objective_node)?;
     ignore_eol(iterator, ast, objective_node)?;
     pull_and_compare_content(iterator, &[Token::RParen('}')], "expected '}'")?;
@@ -157,13 +163,20 @@ pub fn constraint<R: BufRead>(
 ) -> anyhow::Result<()> {
     match iterator.peek() {
         Some(Token::RParen('}')) => Ok(()),
+        // empty or comment-only line between constraints
+        Some(Token::EOL) => {
+            pull_and_compare_content(iterator, &[Token::EOL], "expected line break")?;
+            constraint(iterator, ast, parent)
+        }
         Some(Token::Cmp(_))
         | Some(Token::Num(_))
         | Some(Token::Variable(_, _))
         | Some(Token::ArithOp(_))
+        | Some(Token::Label(_))
         | Some(Token::LParen('['))
         | Some(Token::LParen('(')) => {
             let constraints_node = ast.insert_node(None, Some(parent), ASTNodeTypes::Constraint);
+            label(iterator, ast, constraints_node)?;
             expression(iterator, ast, constraints_node)?;
             ast.insert_node(
                 Some(pull_and_compare_token(
@@ -194,6 +207,188 @@ pub fn constraint<R: BufRead>(
     }
 }
 
+/// Optional `bounds { }` after the constraints
+pub fn bounds<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<()> {
+    while let Some(Token::EOL) = iterator.peek() {
+        iterator.next();
+    }
+    if iterator.peek() != Some(Token::Fun(String::from("bounds"))) {
+        return Ok(());
+    }
+    let bounds_node = ast.insert_node(None, Some(parent), ASTNodeTypes::Bounds);
+    pull_and_compare_content(
+        iterator,
+        &[Token::Fun(String::from("bounds"))],
+        "expected 'bounds'",
+    )?;
+    pull_and_compare_content(iterator, &[Token::LParen('{')], "expected '{'")?;
+    bound(iterator, ast, bounds_node)?;
+    pull_and_compare_content(iterator, &[Token::RParen('}')], "expected '}'")?;
+    Ok(())
+}
+
+/// `lower <= x`, `x <= upper`, `lower <= x <= upper` or `x free`, with
+/// `>=` or `=` in place of `<=` as long as both relations agree
+pub fn bound<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<()> {
+    match iterator.peek() {
+        Some(Token::RParen('}')) => Ok(()),
+        Some(Token::EOL) => {
+            pull_and_compare_content(iterator, &[Token::EOL], "expected line break")?;
+            bound(iterator, ast, parent)
+        }
+        Some(Token::Num(_)) | Some(Token::Variable(_, _)) => {
+            let bound_node = ast.insert_node(None, Some(parent), ASTNodeTypes::Bound);
+            let mut left = None;
+            if let Some(Token::Num(_)) = iterator.peek() {
+                ast.insert_node(iterator.next(), Some(bound_node), ASTNodeTypes::Token);
+                left = Some(bound_cmp(iterator, ast, bound_node)?);
+            }
+            match iterator.next() {
+                Some(Token::Variable(name, coef)) if coef == F64(1.0) => {
+                    ast.insert_node(
+                        Some(Token::Variable(name, coef)),
+                        Some(bound_node),
+                        ASTNodeTypes::Token,
+                    );
+                }
+                t => {
+                    iterator.raise_parsing_error(&format!(
+                        "expected a variable without coefficient but got {:?}",
+                        t
+                    ));
+                    anyhow::bail!("parsing error")
+                }
+            }
+            match (iterator.peek(), &left) {
+                (Some(Token::Fun(f)), None) if f == "free" => {
+                    ast.insert_node(iterator.next(), Some(bound_node), ASTNodeTypes::Token);
+                }
+                (Some(Token::Cmp(_)), _) => {
+                    let right = bound_cmp(iterator, ast, bound_node)?;
+                    if left.is_some_and(|left| left != right || left == CmpOperation::Eq) {
+                        iterator.raise_parsing_error("expected both '<=' or both '>='");
+                        anyhow::bail!("parsing error")
+                    }
+                    ast.insert_node(
+                        Some(pull_and_compare_token(
+                            iterator,
+                            &[Token::Num(F64(0.0))],
+                            "expected number",
+                        )?),
+                        Some(bound_node),
+                        ASTNodeTypes::Token,
+                    );
+                }
+                (_, Some(_)) => {}
+                _ => {
+                    iterator.raise_parsing_error("expected comparison operator or 'free'");
+                    anyhow::bail!("parsing error")
+                }
+            }
+            if iterator.peek() != Some(Token::RParen('}')) {
+                pull_and_compare_content(iterator, &[Token::EOL], "expected line break")?;
+            }
+            bound(iterator, ast, parent)
+        }
+        Some(_) | None => {
+            iterator.raise_parsing_error("expected a bound");
+            anyhow::bail!("parsing error")
+        }
+    }
+}
+
+/// Optional `int { }` and `bin { }` sections after the bounds, in any
+/// order. The first child of each section is its keyword.
+pub fn integers<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<()> {
+    loop {
+        while let Some(Token::EOL) = iterator.peek() {
+            iterator.next();
+        }
+        match iterator.peek() {
+            Some(Token::Fun(f)) if f == "int" || f == "bin" => {}
+            _ => return Ok(()),
+        }
+        let integers_node = ast.insert_node(None, Some(parent), ASTNodeTypes::Integers);
+        ast.insert_node(iterator.next(), Some(integers_node), ASTNodeTypes::Token);
+        pull_and_compare_content(iterator, &[Token::LParen('{')], "expected '{'")?;
+        integer_names(iterator, ast, integers_node)?;
+    }
+}
+
+/// Variable names up to the closing '}', separated by ',' or line breaks
+fn integer_names<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<()> {
+    loop {
+        match iterator.next() {
+            Some(Token::RParen('}')) => return Ok(()),
+            Some(Token::Comma) | Some(Token::EOL) => {}
+            Some(Token::Variable(name, coef)) if coef == F64(1.0) => {
+                ast.insert_node(
+                    Some(Token::Variable(name, coef)),
+                    Some(parent),
+                    ASTNodeTypes::Token,
+                );
+            }
+            t => {
+                iterator.raise_parsing_error(&format!(
+                    "expected a variable without coefficient but got {:?}",
+                    t
+                ));
+                anyhow::bail!("parsing error")
+            }
+        }
+    }
+}
+
+/// Relation of a bound, which may not be strict
+fn bound_cmp<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<CmpOperation> {
+    match iterator.next() {
+        Some(Token::Cmp(op @ (CmpOperation::Leq | CmpOperation::Geq | CmpOperation::Eq))) => {
+            ast.insert_node(
+                Some(Token::Cmp(op.clone())),
+                Some(parent),
+                ASTNodeTypes::Token,
+            );
+            Ok(op)
+        }
+        t => {
+            iterator.raise_parsing_error(&format!("expected '<=', '>=' or '=' but got {:?}", t));
+            anyhow::bail!("parsing error")
+        }
+    }
+}
+
+/// Optional `name:` in front of the objective or a constraint
+pub fn label<R: BufRead>(
+    iterator: &mut TokenizerIterator<R>,
+    ast: &mut AST,
+    parent: usize,
+) -> anyhow::Result<()> {
+    if let Some(Token::Label(_)) = iterator.peek() {
+        ast.insert_node(iterator.next(), Some(parent), ASTNodeTypes::Label);
+    }
+    Ok(())
+}
+
 pub fn expression<R: BufRead>(
     iterator: &mut TokenizerIterator<R>,
     ast: &mut AST,
@@ -214,7 +409,8 @@ pub fn expression_point<R: BufRead>(
         Some(Token::ArithOp(ArithOperation::Add))
         | Some(Token::ArithOp(ArithOperation::Sub))
         | Some(Token::RParen(_))
-        | Some(Token::Cmp(_)) => Ok(()),
+        | Some(Token::Cmp(_))
+        | Some(Token::EOL) => Ok(()),
         Some(Token::Variable(_, _))
         | Some(Token::Num(_))
         | Some(Token::LParen('('))
@@ -253,7 +449,7 @@ pub fn expression_line<R: BufRead>(
     parent: usize,
 ) -> anyhow::Result<()> {
     match iterator.peek() {
-        Some(Token::RParen(_)) | Some(Token::Cmp(_)) => Ok(()),
+        Some(Token::RParen(_)) | Some(Token::Cmp(_)) | Some(Token::EOL) => Ok(()),
         Some(Token::ArithOp(ArithOperation::Sub)) | Some(Token::ArithOp(ArithOperation::Add)) => {
             let expression_node = ast.insert_node(None, Some(parent), ASTNodeTypes::Expression);
             ast.insert_node(
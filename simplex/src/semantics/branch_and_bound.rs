@@ -0,0 +1,604 @@
+use std::rc::Rc;
+
+use crate::{
+    lexer::tokens::CmpOperation,
+    semantics::{
+        program::LinearProgram,
+        revised::RevisedSimplex,
+        simplex::{Method, Simplex, SolverOptions},
+        solution::{Solution, Status},
+        symbols::{Sense, SymbolTable},
+    },
+};
+
+/// Distance to the nearest integer below which a value counts as integer
+const INT_EPS: f64 = 1e-6;
+/// Absolute slack on the gap test, so that a zero gap still prunes ties
+const GAP_EPS: f64 = 1e-9;
+
+/// Order in which the open nodes of the search tree are solved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeSelection {
+    /// newest node first: few open nodes, finds an incumbent early
+    DepthFirst,
+    /// node whose parent has the best relaxation first: proves optimality
+    /// in fewer nodes
+    BestBound,
+}
+
+impl std::str::FromStr for NodeSelection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "depth-first" => Ok(NodeSelection::DepthFirst),
+            "best-bound" => Ok(NodeSelection::BestBound),
+            _ => anyhow::bail!(
+                "unknown node selection '{}', expected depth-first or best-bound",
+                s
+            ),
+        }
+    }
+}
+
+/// Knobs of the branch-and-bound search, the relaxations are solved with `lp`
+#[derive(Debug, Clone)]
+pub struct MipOptions {
+    pub node_selection: NodeSelection,
+    /// Relative optimality gap: a node is dropped once its bound is within
+    /// `gap * |incumbent|` of the incumbent
+    pub gap: f64,
+    /// Maximum number of nodes solved before the search stops with
+    /// `Status::IterationLimit`
+    pub max_nodes: usize,
+    /// Rounds of Gomory cuts added to the root relaxation before branching
+    pub cut_rounds: usize,
+    pub lp: SolverOptions,
+}
+
+impl Default for MipOptions {
+    fn default() -> Self {
+        Self {
+            node_selection: NodeSelection::BestBound,
+            gap: 1e-4,
+            max_nodes: 100_000,
+            cut_rounds: 5,
+            lp: SolverOptions::default(),
+        }
+    }
+}
+
+/// Subproblem of the search: the program with tightened bounds
+#[derive(Clone)]
+struct Node {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    /// objective of the parent's relaxation in maximization form, no
+    /// solution of the node is better
+    bound: f64,
+    /// solved tableau of the parent, shared by both children, and the
+    /// variable whose bound this node tightens; None for the root
+    parent: Option<(Rc<Simplex>, usize)>,
+}
+
+/// Branch-and-bound over the LP relaxation for programs with integer
+/// variables.
+///
+/// Every node is the program with tightened bounds. The root is solved by
+/// the simplex, every other node starts from the optimal tableau of its
+/// parent with the bound of the branching variable moved, which keeps the
+/// basis dual feasible, and is re-optimized by the dual simplex. A node
+/// whose relaxation has a fractional integer variable `x = v` is split into
+/// `x <= floor(v)` and `x >= ceil(v)`, branching on the most fractional
+/// variable. The best integer solution found so far (the incumbent) prunes
+/// every node whose bound is not better by more than the gap.
+///
+/// Before branching, rounds of Gomory cuts tighten the root relaxation, each
+/// re-optimized by the dual simplex as well. The cuts are valid for every
+/// integer solution, so all other nodes inherit them with the tableau.
+pub struct BranchAndBound {
+    program: LinearProgram,
+}
+
+impl BranchAndBound {
+    pub fn new(program: LinearProgram) -> Self {
+        Self { program }
+    }
+
+    pub fn from_symbols(symbols: &SymbolTable) -> anyhow::Result<Self> {
+        let program = LinearProgram::try_from(symbols)?;
+        program.check_bounds()?;
+        Ok(Self::new(program))
+    }
+
+    /// Solve the program. Without integer variables, or if the root
+    /// relaxation is not optimal, this is the solution of the relaxation.
+    /// Otherwise the duals and reduced costs are those of the relaxation in
+    /// which the incumbent was found, without the rows of the cuts, `nodes`
+    /// and `bound` are set and the status is infeasible if no integer
+    /// solution exists. Hitting the node limit reports the iteration limit
+    /// with the incumbent, if any.
+    pub fn solve_with(&self, options: &MipOptions) -> anyhow::Result<Solution> {
+        let sign = match self.program.sense {
+            Sense::Max => 1.0,
+            Sense::Min => -1.0,
+        };
+        // only the root reports infeasibility certificates and a trace, an
+        // infeasible node is just dropped
+        let node_options = SolverOptions {
+            farkas: false,
+            iis: false,
+            trace: false,
+            ..options.lp.clone()
+        };
+
+        let mut open = vec![Node {
+            lower: self.program.lower.clone(),
+            upper: self.program.upper.clone(),
+            bound: f64::INFINITY,
+            parent: None,
+        }];
+        let mut incumbent: Option<(Solution, f64)> = None;
+        // best bound of the nodes dropped within the gap
+        let mut pruned = f64::NEG_INFINITY;
+        let mut nodes = 0;
+        let mut iterations = 0;
+        let mut root_trace = None;
+        let mut status = Status::Optimal;
+        while let Some(mut node) = select(&mut open, options.node_selection) {
+            if let Some((_, value)) = &incumbent {
+                if node.bound <= value + options.gap * value.abs() + GAP_EPS {
+                    pruned = pruned.max(node.bound);
+                    continue;
+                }
+            }
+            if nodes >= options.max_nodes {
+                open.push(node);
+                status = Status::IterationLimit;
+                break;
+            }
+
+            let (simplex, solution) = match node.parent.take() {
+                None if options.lp.method == Method::Revised && !self.has_integers() => {
+                    return RevisedSimplex::new(self.program.clone()).solve_with(&options.lp);
+                }
+                None => {
+                    let mut simplex = Simplex::build(self.program.clone());
+                    let mut solution = simplex.solve_with(&options.lp)?;
+                    if solution.status != Status::Optimal || !self.has_integers() {
+                        return Ok(solution);
+                    }
+                    root_trace = solution.trace.take();
+                    let solution = cut(&mut simplex, solution, &node_options, options.cut_rounds)?;
+                    (simplex, solution)
+                }
+                Some((parent, var)) => {
+                    let mut simplex = Rc::unwrap_or_clone(parent);
+                    simplex.set_bounds(var, node.lower[var], node.upper[var])?;
+                    let solution = simplex.reoptimize(&node_options)?;
+                    (simplex, solution)
+                }
+            };
+            nodes += 1;
+            iterations += solution.iterations;
+            match solution.status {
+                Status::Optimal => {}
+                Status::Infeasible => continue,
+                Status::IterationLimit => {
+                    open.push(node);
+                    status = Status::IterationLimit;
+                    break;
+                }
+                Status::Unbounded => {
+                    anyhow::bail!("relaxation of a bounded node reported unbounded")
+                }
+            }
+
+            let value = sign * solution.objective;
+            if let Some((_, best)) = &incumbent {
+                if value <= best + options.gap * best.abs() + GAP_EPS {
+                    pruned = pruned.max(value);
+                    continue;
+                }
+            }
+            let Some((col, x)) = self.branching_col(&solution) else {
+                incumbent = Some((solution, value));
+                continue;
+            };
+            let mut down = Node {
+                bound: value,
+                parent: Some((Rc::new(simplex), col)),
+                ..node
+            };
+            let mut up = down.clone();
+            down.upper[col] = x.floor();
+            up.lower[col] = x.ceil();
+            // the nearer side is pushed last, depth-first dives into it, a
+            // side outside the bounds of a fractional lower or upper bound is empty
+            let children = if x - x.floor() < 0.5 {
+                [up, down]
+            } else {
+                [down, up]
+            };
+            open.extend(
+                children
+                    .into_iter()
+                    .filter(|n| n.lower[col] <= n.upper[col]),
+            );
+        }
+
+        let bound = open
+            .iter()
+            .map(|n| n.bound)
+            .fold(pruned, f64::max)
+            .max(incumbent.as_ref().map_or(f64::NEG_INFINITY, |(_, v)| *v));
+        let mut solution = match incumbent {
+            Some((mut solution, _)) => {
+                for (name, integer) in self.program.names.iter().zip(&self.program.integer) {
+                    if let (true, Some(value)) = (integer, solution.values.get_mut(name)) {
+                        *value = value.round() + 0.0;
+                    }
+                }
+                // the objective of the rounded values, not of the relaxation
+                let objective: f64 = self
+                    .program
+                    .names
+                    .iter()
+                    .zip(&self.program.obj)
+                    .map(|(name, c)| c * solution.values.get(name).copied().unwrap_or(0.0))
+                    .sum();
+                solution.objective = objective + self.program.obj_constant + 0.0;
+                solution.status = status;
+                solution
+            }
+            None if status == Status::Optimal => Solution::empty(Status::Infeasible, 0),
+            None => Solution::empty(status, 0),
+        };
+        let n_constr = self.program.n_constr();
+        solution.slacks.truncate(n_constr);
+        solution.duals.truncate(n_constr);
+        solution.iterations = iterations;
+        solution.nodes = Some(nodes);
+        solution.trace = root_trace;
+        solution.bound = Some(sign * bound);
+        solution.row_names = (0..self.program.n_constr())
+            .map(|row| self.program.row_label(row))
+            .collect();
+        Ok(solution)
+    }
+
+    fn has_integers(&self) -> bool {
+        self.program.integer.contains(&true)
+    }
+
+    /// Most fractional integer variable of a relaxation with its value, None
+    /// if all of them are integer
+    fn branching_col(&self, solution: &Solution) -> Option<(usize, f64)> {
+        self.program
+            .names
+            .iter()
+            .enumerate()
+            .filter(|(col, _)| self.program.integer[*col])
+            .map(|(col, name)| (col, solution.values[name]))
+            .map(|(col, x)| (col, x, (x - x.round()).abs()))
+            .filter(|(_, _, frac)| *frac > INT_EPS)
+            .fold(
+                None,
+                |best: Option<(usize, f64, f64)>, candidate| match best {
+                    Some(b) if b.2 >= candidate.2 => best,
+                    _ => Some(candidate),
+                },
+            )
+            .map(|(col, x, _)| (col, x))
+    }
+}
+
+/// Add rounds of Gomory cuts to a solved relaxation until a round finds no
+/// cut, does not move the objective or leaves the relaxation not optimal
+fn cut(
+    simplex: &mut Simplex,
+    mut solution: Solution,
+    options: &SolverOptions,
+    rounds: usize,
+) -> anyhow::Result<Solution> {
+    for _ in 0..rounds {
+        let cuts = simplex.gomory_cuts();
+        if cuts.is_empty() {
+            break;
+        }
+        for (coefs, rhs) in cuts {
+            simplex.add_row(None, coefs, CmpOperation::Geq, rhs)?;
+        }
+        let mut next = simplex.reoptimize(options)?;
+        next.iterations += solution.iterations;
+        let moved = (next.objective - solution.objective).abs()
+            > GAP_EPS * (1.0 + solution.objective.abs());
+        solution = next;
+        if solution.status != Status::Optimal || !moved {
+            break;
+        }
+    }
+    Ok(solution)
+}
+
+/// Take the next node to solve off the open list
+fn select(open: &mut Vec<Node>, selection: NodeSelection) -> Option<Node> {
+    match selection {
+        NodeSelection::DepthFirst => open.pop(),
+        NodeSelection::BestBound => {
+            // ties go to the newest node, which dives like depth-first
+            let best = (0..open.len())
+                .rev()
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(b) if open[b].bound >= open[i].bound => best,
+                    _ => Some(i),
+                })?;
+            Some(open.remove(best))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        lexer::tokens::CmpOperation,
+        parser::ast::construct_ast,
+        semantics::{
+            branch_and_bound::{BranchAndBound, MipOptions, NodeSelection},
+            program::LinearProgram,
+            simplex::Simplex,
+            solution::Status,
+            symbols::{Sense, SymbolTable},
+        },
+        test_util::{assert_close, from_dense},
+    };
+
+    fn mip(input: &str) -> BranchAndBound {
+        let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        BranchAndBound::from_symbols(&symbols).unwrap()
+    }
+
+    #[test]
+    fn test_branch_and_bound() {
+        // the relaxation is optimal at x = 3, y = 1.5 with 21
+        let input = "
+max { 5x + 4y }
+st {
+    6x + 4y <= 24
+    x + 2y <= 6
+}
+int { x, y }
+";
+        for node_selection in [NodeSelection::DepthFirst, NodeSelection::BestBound] {
+            let sol = mip(input)
+                .solve_with(&MipOptions {
+                    node_selection,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 20.0);
+            assert_eq!(sol.values["x"], 4.0);
+            assert_eq!(sol.values["y"], 0.0);
+            assert!(sol.nodes.unwrap() > 1);
+            assert!(sol.bound.unwrap() >= 20.0 - 1e-9 && sol.bound.unwrap() < 20.01);
+        }
+
+        // without integer variables it is the relaxation
+        let relaxed = input.replace("int { x, y }", "");
+        let sol = mip(&relaxed).solve_with(&Default::default()).unwrap();
+        assert_close(sol.objective, 21.0);
+        assert_eq!(sol.nodes, None);
+
+        // a gap of 100% keeps the first incumbent, the bound shows how far off it may be
+        let sol = mip(input)
+            .solve_with(&MipOptions {
+                node_selection: NodeSelection::DepthFirst,
+                gap: 1.0,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert!(sol.objective <= 20.0 + 1e-9);
+        assert!(sol.bound.unwrap() >= sol.objective && sol.bound.unwrap() <= 21.0 + 1e-9);
+
+        // the root alone has no integer solution
+        let sol = mip(input)
+            .solve_with(&MipOptions {
+                max_nodes: 1,
+                cut_rounds: 0,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(sol.status, Status::IterationLimit);
+        assert!(sol.values.is_empty());
+        assert_close(sol.bound.unwrap(), 21.0);
+        // but its cuts already close the gap
+        let sol = mip(input)
+            .solve_with(&MipOptions {
+                max_nodes: 1,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_close(sol.bound.unwrap(), 20.0);
+
+        // depth-first keeps branching on the free x2 and never closes the tree
+        let input = "
+min { 3x0 + 4x1 + 3x2 + 3x3 }
+st {
+    -2x0 - 3x2 + 5x1 >= -2
+    6x0 <= 5
+    6x3 - 2x1 <= 4
+    -4x0 - 2x2 - 2x3 <= 6
+}
+bounds { x2 free }
+int { x3, x2 }
+";
+        let sol = mip(input)
+            .solve_with(&MipOptions {
+                node_selection: NodeSelection::DepthFirst,
+                max_nodes: 200,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(sol.status, Status::IterationLimit);
+        assert_eq!(sol.nodes, Some(200));
+        let sol = mip(input).solve_with(&Default::default()).unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert!(sol.nodes.unwrap() <= 10);
+    }
+
+    #[test]
+    fn test_binary_and_infeasible() {
+        // knapsack: the relaxation takes 2/3 of the last item
+        let input = "
+max { 10a + 13b + 7c + 8d }
+st {
+    weight: 4a + 6b + 3c + 5d <= 12
+}
+bin { a, b, c, d }
+";
+        let sol = mip(input).solve_with(&Default::default()).unwrap();
+        assert_close(sol.objective, 25.0);
+        for (var, value) in [("a", 1.0), ("b", 0.0), ("c", 1.0), ("d", 1.0)] {
+            assert_eq!(sol.values[var], value, "{}", var);
+        }
+        assert_eq!(sol.row_names, vec!["weight"]);
+        // the objective is that of the rounded values, with the constant
+        let mut knapsack = mip(input);
+        knapsack.program.obj_constant = 0.5;
+        assert_eq!(
+            knapsack.solve_with(&Default::default()).unwrap().objective,
+            25.5
+        );
+        let sol = mip("min { x }\nst {\nx - y >= -0.5\n}\nint { x }\n")
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_eq!(sol.values["x"], 0.0);
+        assert!(sol.objective == 0.0 && sol.objective.is_sign_positive());
+
+        // feasible relaxation, but no integer point
+        let input = "
+min { x + y }
+st {
+    2x - 2y = 1
+}
+bounds { x <= 10 }
+int { x, y }
+";
+        let sol = mip(input).solve_with(&Default::default()).unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert!(sol.objective.is_nan());
+        assert!(sol.nodes.unwrap() > 1);
+        // an infeasible relaxation keeps its certificate
+        let sol = mip(&input.replace("2x - 2y = 1", "2x + 2y = -3"))
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert!(sol.farkas.is_some());
+        assert_eq!(sol.nodes, None);
+    }
+
+    #[test]
+    fn test_branch_and_bound_random() {
+        let mut seed = 11u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        for _ in 0..40 {
+            let (n_vars, n_constr) = (3, 3);
+            let program = LinearProgram {
+                names: (0..n_vars).map(|i| format!("x{}", i)).collect(),
+                obj: (0..n_vars).map(|_| next(9) as f64 - 3.0).collect(),
+                matrix: from_dense(
+                    n_vars,
+                    &(0..n_constr)
+                        .map(|_| (0..n_vars).map(|_| next(9) as f64 - 3.0).collect())
+                        .collect::<Vec<_>>(),
+                ),
+                rhs: (0..n_constr).map(|_| next(16) as f64 / 2.0 - 2.0).collect(),
+                cmp: (0..n_constr)
+                    .map(|_| match next(3) {
+                        0 => CmpOperation::Geq,
+                        _ => CmpOperation::Leq,
+                    })
+                    .collect(),
+                sense: if next(2) == 0 { Sense::Max } else { Sense::Min },
+                row_names: vec![None; n_constr],
+                obj_name: None,
+                obj_constant: 0.0,
+                lower: (0..n_vars).map(|_| -(next(3) as f64)).collect(),
+                upper: (0..n_vars).map(|_| 1.0 + next(3) as f64).collect(),
+                integer: vec![true; n_vars],
+            };
+
+            // every integer point of the box
+            let mut best: Option<f64> = None;
+            let mut point = program.lower.clone();
+            loop {
+                let feasible = (0..n_constr).all(|row| {
+                    let lhs: f64 = program.matrix.row(row).map(|(j, a)| a * point[j]).sum();
+                    match program.cmp[row] {
+                        CmpOperation::Leq => lhs <= program.rhs[row] + 1e-9,
+                        _ => lhs >= program.rhs[row] - 1e-9,
+                    }
+                });
+                if feasible {
+                    let value: f64 = program.obj.iter().zip(&point).map(|(c, x)| c * x).sum();
+                    best = Some(match (best, program.sense) {
+                        (Some(b), Sense::Max) => b.max(value),
+                        (Some(b), Sense::Min) => b.min(value),
+                        (None, _) => value,
+                    });
+                }
+                let Some(col) = (0..n_vars).find(|c| point[*c] < program.upper[*c]) else {
+                    break;
+                };
+                point[col] += 1.0;
+                point[..col].copy_from_slice(&program.lower[..col]);
+            }
+
+            for (node_selection, cut_rounds) in [
+                (NodeSelection::DepthFirst, 0),
+                (NodeSelection::BestBound, 0),
+                (NodeSelection::BestBound, 5),
+            ] {
+                let sol = BranchAndBound::new(program.clone())
+                    .solve_with(&MipOptions {
+                        node_selection,
+                        gap: 0.0,
+                        cut_rounds,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                match best {
+                    Some(best) => {
+                        assert_eq!(sol.status, Status::Optimal);
+                        assert_close(sol.objective, best);
+                        let value: f64 = program
+                            .names
+                            .iter()
+                            .zip(&program.obj)
+                            .map(|(name, c)| c * sol.values[name])
+                            .sum();
+                        assert_close(value, best);
+                    }
+                    None => assert_eq!(sol.status, Status::Infeasible),
+                }
+            }
+            // the relaxation is never worse than the integer optimum
+            let relaxed = Simplex::build(program.clone())
+                .solve_with(&Default::default())
+                .unwrap();
+            if let (Some(best), Status::Optimal) = (best, relaxed.status) {
+                match program.sense {
+                    Sense::Max => assert!(relaxed.objective >= best - 1e-9),
+                    Sense::Min => assert!(relaxed.objective <= best + 1e-9),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+/// Pivots below this magnitude make the basis matrix singular
+const SINGULAR_EPS: f64 = 1e-11;
+/// Threshold pivoting: any entry within this fraction of the largest one in
+/// its column may be the pivot, the one in the sparsest row is taken
+const PIVOT_THRESHOLD: f64 = 0.1;
+
+/// Basis change since the last factorization: the basis column at `pos` was
+/// replaced by a column that reads `col` in the old basis (`B^-1 a_q`),
+/// kept as the pivot and the other nonzeros
+#[derive(Debug, Clone)]
+struct Eta {
+    pos: usize,
+    pivot: f64,
+    col: Vec<(usize, f64)>,
+}
+
+/// Value of column `col` in a sparse row
+fn entry(row: &[(usize, f64)], col: usize) -> Option<f64> {
+    row.iter().find(|(j, _)| *j == col).map(|(_, a)| *a)
+}
+
+/// Pivot of the next elimination step as row, column and value: in an
+/// active column with the fewest nonzeros, the entry in the sparsest row
+/// among those within `PIVOT_THRESHOLD` of the largest one
+fn next_pivot(
+    rows: &[Vec<(usize, f64)>],
+    col_rows: &[Vec<usize>],
+    col_count: &[usize],
+    row_active: &[bool],
+    col_active: &[bool],
+) -> anyhow::Result<(usize, usize, f64)> {
+    let c = (0..col_active.len())
+        .filter(|j| col_active[*j])
+        .min_by_key(|j| col_count[*j])
+        .expect("an active column in every step");
+    let col = col_rows[c]
+        .iter()
+        .filter(|i| row_active[**i])
+        .filter_map(|i| entry(&rows[*i], c).map(|a| (*i, a)))
+        .collect::<Vec<_>>();
+    let max = col.iter().map(|(_, a)| a.abs()).fold(0.0, f64::max);
+    if max < SINGULAR_EPS {
+        anyhow::bail!("basis matrix is singular");
+    }
+    let (r, pivot) = col
+        .into_iter()
+        .filter(|(_, a)| a.abs() >= PIVOT_THRESHOLD * max)
+        .min_by_key(|(i, _)| rows[*i].len())
+        .expect("the largest entry passes the threshold");
+    Ok((r, c, pivot))
+}
+
+/// Basis matrix of the revised simplex as a sparse LU factorization,
+/// followed by the product form of the basis changes since:
+/// `B^-1 = E_k^-1 ... E_1^-1 B_0^-1`, where `E_i` is the identity with one
+/// column replaced by an eta column.
+///
+/// `B_0` is eliminated one pivot at a time (see `next_pivot`), so slack
+/// columns and other column singletons go first and cost no fill. Step `k`
+/// keeps its multipliers (a column of L) and its pivot row (a row of U),
+/// both over the original row and column indices.
+#[derive(Debug, Clone)]
+pub struct Factorization {
+    /// pivot row and basis position of every step
+    pivots: Vec<(usize, usize)>,
+    /// pivot element of every step
+    diag: Vec<f64>,
+    /// multipliers of every step by row
+    l_cols: Vec<Vec<(usize, f64)>>,
+    /// pivot row of every step by basis position, without the pivot
+    u_rows: Vec<Vec<(usize, f64)>>,
+    etas: Vec<Eta>,
+}
+
+impl Factorization {
+    /// Factorization of the `m x m` identity, the basis of all slack columns
+    pub fn identity(m: usize) -> Self {
+        Self {
+            pivots: (0..m).map(|i| (i, i)).collect(),
+            diag: vec![1.0; m],
+            l_cols: vec![vec![]; m],
+            u_rows: vec![vec![]; m],
+            etas: vec![],
+        }
+    }
+
+    /// LU factorization of a square basis matrix given by its sparse columns
+    pub fn new(cols: &[Vec<(usize, f64)>]) -> anyhow::Result<Self> {
+        let m = cols.len();
+        // the active submatrix by row, and the rows of every column, which
+        // may still list rows that were eliminated since
+        let mut rows: Vec<Vec<(usize, f64)>> = vec![vec![]; m];
+        let mut col_rows: Vec<Vec<usize>> = vec![vec![]; m];
+        for (j, col) in cols.iter().enumerate() {
+            for (i, a) in col {
+                rows[*i].push((j, *a));
+                col_rows[j].push(*i);
+            }
+        }
+        let mut col_count = col_rows.iter().map(|r| r.len()).collect::<Vec<_>>();
+        let mut row_active = vec![true; m];
+        let mut col_active = vec![true; m];
+        let mut work = vec![0.0; m];
+        let mut filled = vec![false; m];
+
+        let mut factor = Self {
+            pivots: Vec::with_capacity(m),
+            diag: Vec::with_capacity(m),
+            l_cols: Vec::with_capacity(m),
+            u_rows: Vec::with_capacity(m),
+            etas: vec![],
+        };
+        for _ in 0..m {
+            let (r, c, pivot) = next_pivot(&rows, &col_rows, &col_count, &row_active, &col_active)?;
+            // the entries of the pivot column in the other active rows
+            let candidates = col_rows[c]
+                .iter()
+                .filter(|i| row_active[**i] && **i != r)
+                .filter_map(|i| entry(&rows[*i], c).map(|a| (*i, a)))
+                .collect::<Vec<_>>();
+            let pivot_row = std::mem::take(&mut rows[r]);
+            row_active[r] = false;
+            col_active[c] = false;
+            for (j, _) in &pivot_row {
+                col_count[*j] -= 1;
+            }
+            let mut l_col = vec![];
+            for (i, a) in candidates {
+                let l = a / pivot;
+                l_col.push((i, l));
+                // row i -= l * pivot row, by scattering it over the columns
+                let mut row = std::mem::take(&mut rows[i]);
+                row.retain(|(j, _)| *j != c);
+                col_count[c] -= 1;
+                for (j, a) in &row {
+                    work[*j] = *a;
+                    filled[*j] = true;
+                }
+                for (j, u) in &pivot_row {
+                    if *j == c {
+                        continue;
+                    }
+                    if !filled[*j] {
+                        filled[*j] = true;
+                        row.push((*j, 0.0));
+                        col_rows[*j].push(i);
+                        col_count[*j] += 1;
+                    }
+                    work[*j] -= l * u;
+                }
+                for (j, a) in row.iter_mut() {
+                    *a = work[*j];
+                    work[*j] = 0.0;
+                    filled[*j] = false;
+                }
+                rows[i] = row;
+            }
+            factor.pivots.push((r, c));
+            factor.diag.push(pivot);
+            factor.l_cols.push(l_col);
+            factor
+                .u_rows
+                .push(pivot_row.into_iter().filter(|(j, _)| *j != c).collect());
+        }
+        Ok(factor)
+    }
+
+    /// Number of basis changes since the factorization
+    pub fn updates(&self) -> usize {
+        self.etas.len()
+    }
+
+    /// Replace the basis column at `pos` by a column that reads `col` in the
+    /// current basis, i.e. the result of `ftran` on it
+    pub fn update(&mut self, pos: usize, col: Vec<f64>) {
+        self.etas.push(Eta {
+            pos,
+            pivot: col[pos],
+            col: col
+                .into_iter()
+                .enumerate()
+                .filter(|(i, a)| *i != pos && *a != 0.0)
+                .collect(),
+        });
+    }
+
+    /// Solve `B x = v`
+    pub fn ftran(&self, v: &[f64]) -> Vec<f64> {
+        // the row operations of the elimination, then back substitution
+        let mut v = v.to_vec();
+        for ((r, _), l_col) in self.pivots.iter().zip(&self.l_cols) {
+            let v_r = v[*r];
+            if v_r != 0.0 {
+                for (i, l) in l_col {
+                    v[*i] -= l * v_r;
+                }
+            }
+        }
+        let mut x = vec![0.0; v.len()];
+        for (k, (r, c)) in self.pivots.iter().enumerate().rev() {
+            let sum: f64 = self.u_rows[k].iter().map(|(j, u)| u * x[*j]).sum();
+            x[*c] = (v[*r] - sum) / self.diag[k];
+        }
+        for eta in &self.etas {
+            let pivot = x[eta.pos] / eta.pivot;
+            if pivot != 0.0 {
+                for (i, a) in &eta.col {
+                    x[*i] -= a * pivot;
+                }
+            }
+            x[eta.pos] = pivot;
+        }
+        x
+    }
+
+    /// Solve `y^T B = c^T`
+    pub fn btran(&self, c: &[f64]) -> Vec<f64> {
+        let mut c = c.to_vec();
+        for eta in self.etas.iter().rev() {
+            let sum: f64 = eta.col.iter().map(|(i, a)| c[*i] * a).sum();
+            c[eta.pos] = (c[eta.pos] - sum) / eta.pivot;
+        }
+        // U^T w = c over the steps, then the transposed row operations
+        let mut y = vec![0.0; c.len()];
+        for (k, (r, col)) in self.pivots.iter().enumerate() {
+            let w = c[*col] / self.diag[k];
+            if w != 0.0 {
+                for (j, u) in &self.u_rows[k] {
+                    c[*j] -= u * w;
+                }
+            }
+            y[*r] = w;
+        }
+        for ((r, _), l_col) in self.pivots.iter().zip(&self.l_cols).rev() {
+            let sum: f64 = l_col.iter().map(|(i, l)| l * y[*i]).sum();
+            y[*r] -= sum;
+        }
+        y
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::{Array, Ix2};
+
+    use crate::{semantics::lu::Factorization, test_util::assert_within};
+
+    fn sparse_cols(b: &Array<f64, Ix2>) -> Vec<Vec<(usize, f64)>> {
+        b.columns()
+            .into_iter()
+            .map(|col| {
+                col.iter()
+                    .enumerate()
+                    .filter(|(_, a)| **a != 0.0)
+                    .map(|(i, a)| (i, *a))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_factorization() {
+        let mut seed = 13u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        // sparse random matrices with a nonzero on a shifted diagonal, each
+        // checked against the explicit matrix through three basis changes
+        for trial in 0..20 {
+            let m = 2 + trial % 7;
+            let mut b: Array<f64, Ix2> = Array::from_shape_fn((m, m), |_| match next(3) {
+                0 => next(9) as f64 - 4.0,
+                _ => 0.0,
+            });
+            for i in 0..m {
+                b[[i, (i + trial) % m]] += 7.0;
+            }
+            let mut factor = Factorization::new(&sparse_cols(&b)).unwrap();
+            let v = (0..m).map(|i| i as f64 - 2.5).collect::<Vec<_>>();
+            for step in 0..4 {
+                let x = factor.ftran(&v);
+                for i in 0..m {
+                    assert_within((0..m).map(|j| b[[i, j]] * x[j]).sum(), v[i], 1e-9);
+                }
+                let y = factor.btran(&v);
+                for j in 0..m {
+                    assert_within((0..m).map(|i| y[i] * b[[i, j]]).sum(), v[j], 1e-9);
+                }
+                if step == 3 {
+                    break;
+                }
+                let col = (0..m).map(|_| next(5) as f64 - 2.0).collect::<Vec<_>>();
+                let alpha = factor.ftran(&col);
+                let pos = (0..m)
+                    .max_by(|a, b| alpha[*a].abs().total_cmp(&alpha[*b].abs()))
+                    .unwrap();
+                if alpha[pos].abs() < 1e-6 {
+                    break;
+                }
+                factor.update(pos, alpha);
+                assert_eq!(factor.updates(), step + 1);
+                b.column_mut(pos).assign(&Array::from(col));
+            }
+        }
+
+        let singular = Array::from_shape_vec((2, 2), vec![1.0, 2.0, 2.0, 4.0]).unwrap();
+        assert!(Factorization::new(&sparse_cols(&singular)).is_err());
+        let x = Factorization::identity(2).ftran(&[3.0, 4.0]);
+        assert_eq!(x, vec![3.0, 4.0]);
+    }
+}
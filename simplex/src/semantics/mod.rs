@@ -1,2 +1,9 @@
+pub mod branch_and_bound;
+pub mod lu;
+pub mod program;
+pub mod revised;
 pub mod simplex;
+pub mod solution;
+pub mod sparse;
 pub mod symbols;
+pub mod trace;
@@ -0,0 +1,202 @@
+use std::collections::HashSet;
+
+use crate::{
+    lexer::tokens::CmpOperation,
+    semantics::{
+        sparse::SparseMatrix,
+        symbols::{Sense, Symbol, SymbolTable, RESERVED_NAMES},
+    },
+};
+
+/// Linear program `opt c^T x st A x (<=, >=, =) b, lower <= x <= upper`
+/// resolved from a symbol table, one entry per variable in `names` order.
+/// Only the nonzeros of `A` are kept.
+#[derive(Debug, Clone)]
+pub struct LinearProgram {
+    pub names: Vec<String>,
+    pub obj: Vec<f64>,
+    /// constraint matrix, a row per constraint and a column per variable
+    pub matrix: SparseMatrix,
+    pub rhs: Vec<f64>,
+    pub cmp: Vec<CmpOperation>,
+    pub sense: Sense,
+    /// `name:` of every row, if given
+    pub row_names: Vec<Option<String>>,
+    pub obj_name: Option<String>,
+    /// constant added to the objective
+    pub obj_constant: f64,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+    /// variables restricted to integer values
+    pub integer: Vec<bool>,
+}
+
+impl LinearProgram {
+    pub fn n_constr(&self) -> usize {
+        self.matrix.n_rows()
+    }
+
+    /// Name of a row for messages and reports, its index if it has none
+    pub fn row_label(&self, row: usize) -> String {
+        match &self.row_names[row] {
+            Some(name) => name.clone(),
+            None => row.to_string(),
+        }
+    }
+
+    /// Name of every row for file formats that need one: rows without a
+    /// name are called `R<index>`, with `_` appended while that is taken
+    pub fn unique_row_names(&self) -> Vec<String> {
+        let taken = self.row_names.iter().flatten().collect::<HashSet<_>>();
+        self.row_names
+            .iter()
+            .enumerate()
+            .map(|(row, name)| match name {
+                Some(name) => name.clone(),
+                None => {
+                    let mut name = format!("R{}", row);
+                    while taken.contains(&name) {
+                        name.push('_');
+                    }
+                    name
+                }
+            })
+            .collect()
+    }
+
+    /// Fails for a variable without a value within its bounds. Such a
+    /// program can still be written, but not solved.
+    pub fn check_bounds(&self) -> anyhow::Result<()> {
+        for (j, name) in self.names.iter().enumerate() {
+            let (lower, upper) = (self.lower[j], self.upper[j]);
+            // the lower bound is shifted away, so it has to be a number
+            if lower > upper || lower == f64::INFINITY || upper == f64::NEG_INFINITY {
+                anyhow::bail!(
+                    "variable '{}' has no value within its bounds [{}, {}]",
+                    name,
+                    lower,
+                    upper
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Program restricted to the given rows, in the given order
+    pub fn subset(&self, rows: &[usize]) -> Self {
+        Self {
+            names: self.names.clone(),
+            obj: self.obj.clone(),
+            matrix: self.matrix.select_rows(rows),
+            rhs: rows.iter().map(|r| self.rhs[*r]).collect(),
+            cmp: rows.iter().map(|r| self.cmp[*r].clone()).collect(),
+            sense: self.sense,
+            row_names: rows.iter().map(|r| self.row_names[*r].clone()).collect(),
+            obj_name: self.obj_name.clone(),
+            obj_constant: self.obj_constant,
+            lower: self.lower.clone(),
+            upper: self.upper.clone(),
+            integer: self.integer.clone(),
+        }
+    }
+}
+
+impl TryFrom<&SymbolTable> for LinearProgram {
+    type Error = anyhow::Error;
+
+    fn try_from(symbols: &SymbolTable) -> anyhow::Result<Self> {
+        symbols.check_names()?;
+        let n_constr = symbols.n_constr as usize;
+
+        // sort variables so that the column layout does not depend on hashing
+        let mut names = symbols
+            .table
+            .keys()
+            .filter(|k| !RESERVED_NAMES.contains(&k.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        let n_vars = names.len();
+
+        let mut entries = vec![];
+        let mut rhs = vec![0.0; n_constr];
+        let mut cmp = vec![None; n_constr];
+        let mut obj = vec![0.0; n_vars];
+        for value_arr in symbols.table.values() {
+            for value in value_arr {
+                match value {
+                    Symbol::Obj(_) | Symbol::Constr(_, _) => {}
+                    Symbol::RHS(row, value) => rhs[*row as usize] = value.0,
+                    Symbol::Cmp(row, op) => cmp[*row as usize] = Some(op.clone()),
+                }
+            }
+        }
+        for (i, name) in names.iter().enumerate() {
+            for value in &symbols.table[name] {
+                match value {
+                    Symbol::Obj(value) => obj[i] += value.0,
+                    Symbol::Constr(row, value) => entries.push((*row as usize, i, value.0)),
+                    _ => {}
+                }
+            }
+        }
+
+        let row_names = (0..n_constr as u32)
+            .map(|row| symbols.row_names.get(&row).cloned())
+            .collect::<Vec<_>>();
+        let mut seen = HashSet::new();
+        for name in row_names.iter().flatten() {
+            if !seen.insert(name) {
+                anyhow::bail!("constraint name '{}' is used more than once", name);
+            }
+        }
+        let label = |row: usize| match &row_names[row] {
+            Some(name) => name.clone(),
+            None => row.to_string(),
+        };
+
+        // strict inequalities have no optimum in general (the supremum of
+        // max x st x < 1 is never attained), so they are rejected up front
+        let cmp = cmp
+            .into_iter()
+            .enumerate()
+            .map(|(row, op)| match op {
+                Some(CmpOperation::Lt) => anyhow::bail!(
+                    "constraint {}: strict inequality '<' is not supported, use '<=' instead",
+                    label(row)
+                ),
+                Some(CmpOperation::Gt) => anyhow::bail!(
+                    "constraint {}: strict inequality '>' is not supported, use '>=' instead",
+                    label(row)
+                ),
+                Some(op) => Ok(op),
+                None => anyhow::bail!("constraint {} has no comparison operator", label(row)),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let (lower, upper) = names
+            .iter()
+            .map(|name| {
+                let bounds = symbols.bounds(name);
+                (bounds.lower, bounds.upper)
+            })
+            .unzip();
+
+        let integer = names.iter().map(|n| symbols.integers.contains(n)).collect();
+
+        Ok(Self {
+            names,
+            obj,
+            matrix: SparseMatrix::from_triplets(n_constr, n_vars, entries),
+            rhs,
+            cmp,
+            sense: symbols.sense,
+            row_names,
+            obj_name: symbols.obj_name.clone(),
+            obj_constant: symbols.obj_constant,
+            lower,
+            upper,
+            integer,
+        })
+    }
+}
@@ -0,0 +1,738 @@
+use std::collections::HashMap;
+
+use crate::{
+    lexer::tokens::CmpOperation,
+    semantics::{
+        lu::Factorization,
+        program::LinearProgram,
+        simplex::{PivotRule, Simplex, SolverOptions},
+        solution::{Solution, Status},
+        sparse::SparseMatrix,
+        symbols::Sense,
+    },
+};
+
+/// Entries of the entering column below this magnitude are no pivot
+const PIVOT_EPS: f64 = 1e-9;
+/// Tolerance on the bounds of the basic variables
+const FEAS_EPS: f64 = 1e-7;
+/// Tolerance on the reduced costs
+const OPT_EPS: f64 = 1e-9;
+/// Basis changes kept in product form before the basis is factorized again
+const REFACTOR_EVERY: usize = 64;
+/// Devex weights are reset to 1 once one of them grows beyond this
+const DEVEX_RESET: f64 = 1e6;
+
+/// Where a variable sits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Basic,
+    AtLower,
+    AtUpper,
+    /// nonbasic free variable, at zero
+    Zero,
+}
+
+/// Result of a pivoting loop
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Optimal,
+    /// the entering variable moves along the direction without bound
+    Unbounded(usize, f64),
+    /// Phase I ended with an infeasible basis, with the duals of its objective
+    Infeasible(Vec<f64>),
+    IterationLimit,
+}
+
+/// Revised simplex with bounded variables.
+///
+/// Every row gets a slack, `A x + s = b`, bounded by its relation: `s >= 0`
+/// for `<=`, `s <= 0` for `>=` and `s = 0` for `=` rows, so the slacks form
+/// the starting basis. Instead of a tableau only the sparse matrix `[A I]`
+/// and a factorization of the basis matrix are kept (see `Factorization`);
+/// the duals `y = c_B B^-1` and the entering column `B^-1 a_q` are solved
+/// for in every iteration and the reduced costs `c - A^T y` are priced row
+/// by row. A nonbasic variable sits at one of its bounds, a free one at
+/// zero.
+///
+/// Phase I minimizes the sum of the bound violations of the basic variables,
+/// with the costs set anew in every iteration. A variable outside its bounds
+/// may move up to the bound it violates, the others stay within theirs.
+///
+/// `SteepestEdge` is approximated by Devex pricing: `d_j^2 / w_j` with
+/// reference weights `w_j` updated from the pivot row, which costs one more
+/// solve per pivot instead of a column norm per candidate. `Lexicographic`
+/// prices like `Dantzig`. There is no tableau to trace and no Phase I
+/// variant to choose.
+pub struct RevisedSimplex {
+    program: LinearProgram,
+    /// the columns of the variables, then one slack column per row
+    matrix: SparseMatrix,
+    /// objective of every column in minimization form
+    cost: Vec<f64>,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    /// value of every column
+    x: Vec<f64>,
+    state: Vec<State>,
+    /// basic column of every row
+    basis: Vec<usize>,
+    factor: Factorization,
+    /// Devex reference weight of every column
+    weights: Vec<f64>,
+    iterations: usize,
+}
+
+impl RevisedSimplex {
+    pub fn new(program: LinearProgram) -> Self {
+        let (m, n) = (program.n_constr(), program.names.len());
+        let slacks = (0..m).map(|i| (i, n + i, 1.0));
+        let matrix = SparseMatrix::from_triplets(m, n + m, program.matrix.entries().chain(slacks));
+        let sign = match program.sense {
+            Sense::Max => -1.0,
+            Sense::Min => 1.0,
+        };
+        let mut cost = program.obj.iter().map(|c| sign * c).collect::<Vec<_>>();
+        cost.resize(n + m, 0.0);
+        let mut lower = program.lower.clone();
+        let mut upper = program.upper.clone();
+        for cmp in &program.cmp {
+            let (l, u) = match cmp {
+                CmpOperation::Leq => (0.0, f64::INFINITY),
+                CmpOperation::Geq => (f64::NEG_INFINITY, 0.0),
+                _ => (0.0, 0.0),
+            };
+            lower.push(l);
+            upper.push(u);
+        }
+
+        let mut x = vec![0.0; n + m];
+        let mut state = vec![State::Basic; n + m];
+        for j in 0..n {
+            (x[j], state[j]) = if lower[j].is_finite() {
+                (lower[j], State::AtLower)
+            } else if upper[j].is_finite() {
+                (upper[j], State::AtUpper)
+            } else {
+                (0.0, State::Zero)
+            };
+        }
+        let mut simplex = Self {
+            program,
+            matrix,
+            cost,
+            lower,
+            upper,
+            x,
+            state,
+            basis: (n..n + m).collect(),
+            factor: Factorization::identity(m),
+            weights: vec![1.0; n + m],
+            iterations: 0,
+        };
+        simplex.compute_basic();
+        simplex
+    }
+
+    fn n_constr(&self) -> usize {
+        self.basis.len()
+    }
+
+    /// Column as a dense vector over the rows
+    fn dense_col(&self, col: usize) -> Vec<f64> {
+        let mut dense = vec![0.0; self.n_constr()];
+        for (i, a) in self.matrix.col(col) {
+            dense[i] = a;
+        }
+        dense
+    }
+
+    /// Basic values `x_B = B^-1 (b - N x_N)` from the nonbasic ones
+    fn compute_basic(&mut self) {
+        let mut r = self.program.rhs.clone();
+        for (col, x) in self.x.iter().enumerate() {
+            if self.state[col] != State::Basic && *x != 0.0 {
+                for (i, a) in self.matrix.col(col) {
+                    r[i] -= a * x;
+                }
+            }
+        }
+        let x_b = self.factor.ftran(&r);
+        for (col, x) in self.basis.iter().zip(x_b) {
+            self.x[*col] = x;
+        }
+    }
+
+    /// Factorize the basis matrix again, dropping the product form
+    fn refactor(&mut self) -> anyhow::Result<()> {
+        let b = self
+            .basis
+            .iter()
+            .map(|col| self.matrix.col(*col).collect())
+            .collect::<Vec<_>>();
+        self.factor = Factorization::new(&b)?;
+        self.compute_basic();
+        Ok(())
+    }
+
+    /// Phase I costs: -1 below the lower bound, +1 above the upper bound.
+    /// None if the basis is feasible.
+    fn infeasibility_costs(&self) -> Option<Vec<f64>> {
+        let mut infeasible = false;
+        let mut cost = vec![0.0; self.cost.len()];
+        for col in &self.basis {
+            let x = self.x[*col];
+            if x < self.lower[*col] - FEAS_EPS {
+                cost[*col] = -1.0;
+                infeasible = true;
+            } else if x > self.upper[*col] + FEAS_EPS {
+                cost[*col] = 1.0;
+                infeasible = true;
+            }
+        }
+        infeasible.then_some(cost)
+    }
+
+    /// Duals `y = c_B B^-1` for the costs `cost`
+    fn duals(&self, cost: &[f64]) -> Vec<f64> {
+        let c_b = self.basis.iter().map(|col| cost[*col]).collect::<Vec<_>>();
+        self.factor.btran(&c_b)
+    }
+
+    fn reduced_cost(&self, cost: &[f64], y: &[f64], col: usize) -> f64 {
+        cost[col] - self.matrix.col_dot(col, y)
+    }
+
+    /// Entering column and the direction it moves in. Fixed columns never
+    /// enter.
+    fn entering_col(&self, cost: &[f64], y: &[f64], rule: PivotRule) -> Option<(usize, f64)> {
+        let y_a = self.matrix.transpose_mul(y);
+        let mut best: Option<(usize, f64, f64)> = None;
+        for (col, y_a) in y_a.into_iter().enumerate() {
+            if self.state[col] == State::Basic || self.lower[col] == self.upper[col] {
+                continue;
+            }
+            let d = cost[col] - y_a;
+            let dir = match self.state[col] {
+                State::AtLower if d < -OPT_EPS => 1.0,
+                State::AtUpper if d > OPT_EPS => -1.0,
+                State::Zero if d.abs() > OPT_EPS => -d.signum(),
+                _ => continue,
+            };
+            if rule == PivotRule::Bland {
+                return Some((col, dir));
+            }
+            let score = match rule {
+                PivotRule::SteepestEdge => d * d / self.weights[col],
+                _ => d.abs(),
+            };
+            if best.is_none_or(|(_, _, b)| score > b) {
+                best = Some((col, dir, score));
+            }
+        }
+        best.map(|(col, dir, _)| (col, dir))
+    }
+
+    /// Ratio test along `x_B + t * delta`: the row whose basic variable hits
+    /// a bound first with that bound, None if the entering variable reaches
+    /// its own other bound first or nothing stops it. Basic variables outside
+    /// their bounds in Phase I may move up to the bound they violate.
+    fn leaving_row(
+        &self,
+        col: usize,
+        delta: &[f64],
+        rule: PivotRule,
+    ) -> (Option<(usize, f64)>, f64) {
+        let mut best: Option<(usize, f64)> = None;
+        let mut best_t = self.upper[col] - self.lower[col];
+        for (pos, d) in delta.iter().enumerate() {
+            if d.abs() < PIVOT_EPS {
+                continue;
+            }
+            let b = self.basis[pos];
+            let (x, lower, upper) = (self.x[b], self.lower[b], self.upper[b]);
+            let bound = if x < lower - FEAS_EPS {
+                if *d < 0.0 {
+                    continue;
+                }
+                lower
+            } else if x > upper + FEAS_EPS {
+                if *d > 0.0 {
+                    continue;
+                }
+                upper
+            } else if *d < 0.0 {
+                lower
+            } else {
+                upper
+            };
+            if !bound.is_finite() {
+                continue;
+            }
+            let t = ((bound - x) / d).max(0.0);
+            let better = match best {
+                None => t < best_t,
+                Some((best_pos, _)) if (t - best_t).abs() <= PIVOT_EPS => match rule {
+                    PivotRule::Bland => b < self.basis[best_pos],
+                    _ => d.abs() > delta[best_pos].abs(),
+                },
+                Some(_) => t < best_t,
+            };
+            if better {
+                best = Some((pos, bound));
+                best_t = t;
+            }
+        }
+        (best, best_t)
+    }
+
+    /// Pivot until the Phase I or Phase II objective can not be improved
+    fn iterate(&mut self, phase_one: bool, options: &SolverOptions) -> anyhow::Result<Outcome> {
+        let max_iter = options
+            .max_iter
+            .unwrap_or(50 * (self.n_constr() + 1) * (self.matrix.n_cols() + 2));
+        let mut degenerate = 0;
+        loop {
+            let cost = if phase_one {
+                match self.infeasibility_costs() {
+                    Some(cost) => cost,
+                    None => return Ok(Outcome::Optimal),
+                }
+            } else {
+                self.cost.clone()
+            };
+            let rule = match options.bland_after {
+                Some(n) if degenerate >= n => PivotRule::Bland,
+                _ => options.pivot_rule,
+            };
+            let y = self.duals(&cost);
+            let entering = self.entering_col(&cost, &y, rule);
+            let Some((col, dir)) = entering else {
+                return Ok(match phase_one {
+                    true => Outcome::Infeasible(y),
+                    false => Outcome::Optimal,
+                });
+            };
+            if self.iterations >= max_iter {
+                return Ok(Outcome::IterationLimit);
+            }
+
+            let alpha = self.factor.ftran(&self.dense_col(col));
+            let delta = alpha.iter().map(|a| -dir * a).collect::<Vec<_>>();
+            let (leaving, t) = self.leaving_row(col, &delta, rule);
+            if leaving.is_none() && t.is_infinite() {
+                if phase_one {
+                    anyhow::bail!("phase I objective is bounded but reported unbounded");
+                }
+                return Ok(Outcome::Unbounded(col, dir));
+            }
+            if t <= OPT_EPS {
+                degenerate += 1;
+            } else {
+                degenerate = 0;
+            }
+            self.x[col] += dir * t;
+            for (b, d) in self.basis.iter().zip(&delta) {
+                self.x[*b] += d * t;
+            }
+            self.iterations += 1;
+            let Some((pos, bound)) = leaving else {
+                // the entering variable moved to its other bound
+                self.state[col] = match self.state[col] {
+                    State::AtLower => State::AtUpper,
+                    _ => State::AtLower,
+                };
+                continue;
+            };
+            let out = self.basis[pos];
+            if rule == PivotRule::SteepestEdge {
+                self.update_weights(pos, col, alpha[pos]);
+            }
+            self.x[out] = bound;
+            self.state[out] = if bound == self.lower[out] {
+                State::AtLower
+            } else {
+                State::AtUpper
+            };
+            self.state[col] = State::Basic;
+            self.basis[pos] = col;
+            self.factor.update(pos, alpha);
+            if self.factor.updates() >= REFACTOR_EVERY {
+                self.refactor()?;
+            }
+        }
+    }
+
+    /// Devex update for `col` entering the basis in `pos` with the pivot
+    /// element `pivot`, from the pivot row `e_pos^T B^-1 A` of the old basis
+    fn update_weights(&mut self, pos: usize, col: usize, pivot: f64) {
+        let mut e = vec![0.0; self.n_constr()];
+        e[pos] = 1.0;
+        let rho = self.factor.btran(&e);
+        let row = self.matrix.transpose_mul(&rho);
+        let w_q = self.weights[col];
+        for (j, a) in row.into_iter().enumerate() {
+            if self.state[j] == State::Basic || j == col {
+                continue;
+            }
+            let w = (a / pivot).powi(2) * w_q;
+            if w > self.weights[j] {
+                self.weights[j] = w;
+            }
+        }
+        self.weights[self.basis[pos]] = (w_q / (pivot * pivot)).max(1.0);
+        if self.weights.iter().any(|w| *w > DEVEX_RESET) {
+            self.weights.iter_mut().for_each(|w| *w = 1.0);
+        }
+    }
+
+    /// Farkas multipliers of the rows of an infeasible program, the negated
+    /// duals of the Phase I objective. None if the program is feasible.
+    pub fn farkas(program: &LinearProgram) -> Option<Vec<f64>> {
+        let mut simplex = Self::new(program.clone());
+        match simplex.iterate(true, &SolverOptions::default()) {
+            Ok(Outcome::Infeasible(y)) => Some(y.iter().map(|y| -y).collect()),
+            _ => None,
+        }
+    }
+
+    /// Solve the program. Only `max_iter`, `farkas`, `iis`, `pivot_rule` and
+    /// `bland_after` of the options apply.
+    pub fn solve_with(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        let mut solution = match self.iterate(true, options)? {
+            Outcome::Infeasible(y) => {
+                let mut solution = Solution::empty(Status::Infeasible, self.iterations);
+                if options.farkas {
+                    let farkas = y.iter().map(|y| -y).collect::<Vec<_>>();
+                    if options.iis {
+                        solution.iis = Some(Simplex::iis(&self.program, &farkas, Self::farkas));
+                    }
+                    solution.farkas = Some(farkas);
+                }
+                solution
+            }
+            Outcome::IterationLimit => Solution::empty(Status::IterationLimit, self.iterations),
+            _ => match self.iterate(false, options)? {
+                Outcome::Unbounded(col, dir) => {
+                    let mut solution = self.solution(Status::Unbounded);
+                    solution.ray = Some(self.ray(col, dir));
+                    solution
+                }
+                Outcome::IterationLimit => self.solution(Status::IterationLimit),
+                _ => self.solution(Status::Optimal),
+            },
+        };
+        solution.row_names = (0..self.program.n_constr())
+            .map(|row| self.program.row_label(row))
+            .collect();
+        Ok(solution)
+    }
+
+    /// Collect the current basic solution into a `Solution` with the given status
+    fn solution(&self, status: Status) -> Solution {
+        let sign = match self.program.sense {
+            Sense::Max => -1.0,
+            Sense::Min => 1.0,
+        };
+        let n = self.program.names.len();
+        let objective = match status {
+            Status::Unbounded => -sign * f64::INFINITY,
+            _ => {
+                let value: f64 = (0..n).map(|j| self.program.obj[j] * self.x[j]).sum();
+                self.program.obj_constant + value
+            }
+        };
+        let y = self.duals(&self.cost);
+        let slacks = self
+            .program
+            .cmp
+            .iter()
+            .enumerate()
+            .map(|(i, cmp)| match cmp {
+                CmpOperation::Leq => self.x[n + i],
+                CmpOperation::Geq => -self.x[n + i],
+                _ => 0.0,
+            })
+            .collect();
+
+        Solution {
+            status,
+            objective,
+            values: self
+                .program
+                .names
+                .iter()
+                .zip(&self.x)
+                .map(|(name, x)| (name.clone(), *x))
+                .collect(),
+            slacks,
+            duals: y.iter().map(|y| sign * y).collect(),
+            reduced_costs: self
+                .program
+                .names
+                .iter()
+                .enumerate()
+                .map(|(j, name)| (name.clone(), sign * self.reduced_cost(&self.cost, &y, j)))
+                .collect(),
+            iterations: self.iterations,
+            ray: None,
+            farkas: None,
+            iis: None,
+            trace: None,
+            row_names: vec![],
+            nodes: None,
+            bound: None,
+        }
+    }
+
+    /// Extreme ray along the entering column `col` moving in direction `dir`,
+    /// the basic variables change by the negated entries of `B^-1 a_q`
+    fn ray(&self, col: usize, dir: f64) -> HashMap<String, f64> {
+        let mut direction = vec![0.0; self.matrix.n_cols()];
+        direction[col] = dir;
+        let alpha = self.factor.ftran(&self.dense_col(col));
+        for (b, a) in self.basis.iter().zip(alpha) {
+            direction[*b] = -dir * a;
+        }
+        self.program
+            .names
+            .iter()
+            .zip(direction)
+            .map(|(name, d)| (name.clone(), d))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        lexer::tokens::CmpOperation,
+        parser::ast::construct_ast,
+        semantics::{
+            program::LinearProgram,
+            revised::RevisedSimplex,
+            simplex::{PivotRule, Simplex, SolverOptions},
+            solution::Status,
+            symbols::{Sense, SymbolTable},
+        },
+        test_util::{assert_close, from_dense},
+    };
+
+    fn revised(input: &str) -> RevisedSimplex {
+        let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        RevisedSimplex::new(LinearProgram::try_from(&symbols).unwrap())
+    }
+
+    #[test]
+    fn test_revised_simplex() {
+        let input = "
+max { 3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+        let sol = revised(input).solve_with(&Default::default()).unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert_close(sol.objective, 36.0);
+        assert_close(sol.values["x1"], 2.0);
+        assert_close(sol.values["x2"], 6.0);
+        assert_close(sol.slacks[0], 2.0);
+        for (dual, expected) in sol.duals.iter().zip([0.0, 1.5, 1.0]) {
+            assert_close(*dual, expected);
+        }
+
+        let input = "
+min { 2x + 3y }
+st {
+    x + y >= 4
+    x + 3y >= 6
+    x - y = 3
+}
+bounds { y <= 10 }
+";
+        let sol = revised(input).solve_with(&Default::default()).unwrap();
+        assert_close(sol.objective, 9.75);
+        assert_close(sol.values["x"], 3.75);
+        for (dual, expected) in sol.duals.iter().zip([0.0, 1.25, 0.75]) {
+            assert_close(*dual, expected);
+        }
+        assert_close(sol.slacks[0], 0.5);
+        assert_close(sol.reduced_costs["y"], 0.0);
+
+        let input = "
+max { x1 + x2 }
+st {
+    x1 - x2 <= 1
+}
+bounds { x2 free }
+";
+        let sol = revised(input).solve_with(&Default::default()).unwrap();
+        assert_eq!(sol.status, Status::Unbounded);
+        assert_eq!(sol.objective, f64::INFINITY);
+        let ray = sol.ray.unwrap();
+        assert!(ray["x1"] + ray["x2"] > 0.0);
+        assert!(ray["x1"] - ray["x2"] <= 1e-9);
+
+        let input = "
+max {x1 + x2 }
+st {
+    x1 + x2 <= 2
+    x1 >= 0.5
+    x1 + x2 >= 3
+    x2 <= 10
+}
+";
+        let sol = revised(input).solve_with(&Default::default()).unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert_eq!(sol.iis, Some(vec![0, 2]));
+        let y = sol.farkas.unwrap();
+        assert!(y[0] > 0.0 && y[2] < 0.0);
+        assert_close(y[0] * 2.0 + y[2] * 3.0 + y[1] * 0.5 + y[3] * 10.0, -1.0);
+    }
+
+    #[test]
+    fn test_revised_simplex_random() {
+        // random programs with bounds and all relations, against the tableau;
+        // the larger ones refactorize the basis on the way
+        let mut seed = 17u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let mut statuses = vec![];
+        for round in 0..120 {
+            let (n_vars, n_constr) = if round % 10 == 0 { (60, 30) } else { (4, 3) };
+            let lower = (0..n_vars)
+                .map(|_| match next(4) {
+                    0 => -(next(4) as f64),
+                    1 => f64::NEG_INFINITY,
+                    _ => 0.0,
+                })
+                .collect::<Vec<_>>();
+            let program = LinearProgram {
+                names: (0..n_vars).map(|i| format!("x{}", i)).collect(),
+                obj: (0..n_vars).map(|_| next(9) as f64 - 4.0).collect(),
+                matrix: from_dense(
+                    n_vars,
+                    &(0..n_constr)
+                        .map(|_| (0..n_vars).map(|_| next(7) as f64 - 2.0).collect())
+                        .collect::<Vec<_>>(),
+                ),
+                rhs: (0..n_constr).map(|_| next(20) as f64 - 4.0).collect(),
+                cmp: (0..n_constr)
+                    .map(|_| match next(6) {
+                        0 => CmpOperation::Eq,
+                        1 | 2 => CmpOperation::Geq,
+                        _ => CmpOperation::Leq,
+                    })
+                    .collect(),
+                sense: if next(2) == 0 { Sense::Max } else { Sense::Min },
+                row_names: vec![None; n_constr],
+                obj_name: None,
+                obj_constant: 0.0,
+                upper: lower
+                    .iter()
+                    .map(|lower| match next(3) {
+                        0 => f64::INFINITY,
+                        _ => lower.max(-3.0) + 1.0 + next(6) as f64,
+                    })
+                    .collect(),
+                lower,
+                integer: vec![false; n_vars],
+            };
+            let options = SolverOptions {
+                iis: false,
+                pivot_rule: if next(4) == 0 {
+                    PivotRule::Bland
+                } else {
+                    PivotRule::Dantzig
+                },
+                ..Default::default()
+            };
+            let truth = Simplex::build(program.clone())
+                .solve_with(&Default::default())
+                .unwrap();
+            let sol = RevisedSimplex::new(program.clone())
+                .solve_with(&options)
+                .unwrap();
+            assert_eq!(sol.status, truth.status);
+            statuses.push(sol.status);
+            let activity = |row: usize, x: &dyn Fn(usize) -> f64| -> f64 {
+                program.matrix.row(row).map(|(j, a)| a * x(j)).sum()
+            };
+            match sol.status {
+                Status::Optimal => {
+                    assert_close(sol.objective, truth.objective);
+                    let x = |j: usize| sol.values[&program.names[j]];
+                    for j in 0..n_vars {
+                        assert!(x(j) >= program.lower[j] - 1e-6 && x(j) <= program.upper[j] + 1e-6);
+                    }
+                    for row in 0..n_constr {
+                        let slack = program.rhs[row] - activity(row, &x);
+                        match program.cmp[row] {
+                            CmpOperation::Leq => assert_close(sol.slacks[row], slack),
+                            CmpOperation::Geq => assert_close(sol.slacks[row], -slack),
+                            _ => assert_close(slack, 0.0),
+                        }
+                        // only tight rows have a dual
+                        assert!(sol.duals[row].abs() < 1e-6 || sol.slacks[row].abs() < 1e-6);
+                    }
+                    // strong duality with the reduced costs at the bounds
+                    let dual: f64 = (0..n_constr)
+                        .map(|row| sol.duals[row] * program.rhs[row])
+                        .chain((0..n_vars).map(|j| sol.reduced_costs[&program.names[j]] * x(j)))
+                        .sum();
+                    assert_close(dual, sol.objective);
+                }
+                Status::Unbounded => {
+                    let ray = sol.ray.unwrap();
+                    let d = |j: usize| ray[&program.names[j]];
+                    let gain: f64 = (0..n_vars).map(|j| program.obj[j] * d(j)).sum();
+                    match program.sense {
+                        Sense::Max => assert!(gain > 1e-9),
+                        Sense::Min => assert!(gain < -1e-9),
+                    }
+                    for row in 0..n_constr {
+                        let a_d = activity(row, &d);
+                        match program.cmp[row] {
+                            CmpOperation::Leq => assert!(a_d <= 1e-6),
+                            CmpOperation::Geq => assert!(a_d >= -1e-6),
+                            _ => assert_close(a_d, 0.0),
+                        }
+                    }
+                }
+                Status::Infeasible => {
+                    // y^T A x <= y^T b holds for every solution of the rows,
+                    // but not for any x in the bounds
+                    let y = sol.farkas.unwrap();
+                    let mut min_lhs = 0.0;
+                    for j in 0..n_vars {
+                        let y_a: f64 = program.matrix.col_dot(j, &y);
+                        if y_a > 1e-9 {
+                            min_lhs += y_a * program.lower[j];
+                        } else if y_a < -1e-9 {
+                            min_lhs += y_a * program.upper[j];
+                        }
+                    }
+                    let y_b: f64 = (0..n_constr).map(|r| y[r] * program.rhs[r]).sum();
+                    assert!(min_lhs > y_b + 1e-9, "{} <= {}", min_lhs, y_b);
+                    for (row, cmp) in program.cmp.iter().enumerate() {
+                        match cmp {
+                            CmpOperation::Leq => assert!(y[row] >= -1e-9),
+                            CmpOperation::Geq => assert!(y[row] <= 1e-9),
+                            _ => {}
+                        }
+                    }
+                }
+                Status::IterationLimit => panic!("iteration limit"),
+            }
+        }
+        for status in [Status::Optimal, Status::Infeasible, Status::Unbounded] {
+            assert!(statuses.contains(&status), "{}", status);
+        }
+    }
+}
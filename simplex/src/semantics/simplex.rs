@@ -1,83 +1,2802 @@
 use std::collections::HashMap;
 
-use ndarray::{Array, Ix1, Ix2};
+use ndarray::{s, Array, Ix2};
 
 use crate::{
-    parser::ast::{ASTNodeTypes, AST},
-    semantics::symbols::{Symbol, SymbolTable},
+    lexer::tokens::CmpOperation,
+    semantics::{
+        program::LinearProgram,
+        solution::{Solution, Status},
+        symbols::Sense,
+        trace::{Phase, Trace, TraceStep},
+    },
 };
 
+/// Tolerance used for all pivoting decisions on the tableau
+const EPS: f64 = 1e-9;
+/// Tolerance on the phase I objective to decide feasibility
+const FEAS_EPS: f64 = 1e-7;
+/// Fractional part of a basic value a Gomory cut is derived from must lie
+/// in `[GOMORY_MIN_FRAC, 1 - GOMORY_MIN_FRAC]`, nearer ones give weak and
+/// badly scaled cuts
+const GOMORY_MIN_FRAC: f64 = 1e-3;
+/// Cuts with a coefficient larger than this are dropped as numerically unsafe
+const GOMORY_MAX_COEF: f64 = 1e6;
+
+/// How a feasible starting basis is found for rows without a slack column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitStrategy {
+    /// Phase I on an auxiliary objective, then Phase II on the real one
+    TwoPhase,
+    /// Penalize every artificial variable with the given M in the objective
+    BigM(f64),
+}
+
+impl InitStrategy {
+    /// M of `big-m` when read from a string, large against the objective
+    /// coefficients of most programs
+    pub const DEFAULT_BIG_M: f64 = 1e6;
+}
+
+impl std::str::FromStr for InitStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "two-phase" => Ok(InitStrategy::TwoPhase),
+            "big-m" => Ok(InitStrategy::BigM(Self::DEFAULT_BIG_M)),
+            _ => anyhow::bail!("unknown init '{}', expected two-phase or big-m", s),
+        }
+    }
+}
+
+/// Choice of the entering variable and the leaving row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotRule {
+    /// most negative reduced cost
+    Dantzig,
+    /// smallest column index with negative reduced cost, ties in the ratio
+    /// test go to the smallest basic column index; never cycles
+    Bland,
+    /// largest improvement per unit length of the edge, `d_j^2 / (1 + |B^-1 a_j|^2)`
+    SteepestEdge,
+    /// Dantzig's entering column with ties in the ratio test broken
+    /// lexicographically on the rows of `B^-1`; never cycles
+    Lexicographic,
+}
+
+impl std::str::FromStr for PivotRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "dantzig" => Ok(PivotRule::Dantzig),
+            "bland" => Ok(PivotRule::Bland),
+            "steepest-edge" => Ok(PivotRule::SteepestEdge),
+            "lexicographic" => Ok(PivotRule::Lexicographic),
+            _ => anyhow::bail!(
+                "unknown pivot rule '{}', expected dantzig, bland, steepest-edge or lexicographic",
+                s
+            ),
+        }
+    }
+}
+
+/// Simplex variant that solves the program from its starting basis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// keeps the basis feasible and improves the objective
+    Primal,
+    /// keeps the reduced costs optimal and removes infeasibilities; needs a
+    /// dual feasible starting basis, without one it falls back to `Primal`
+    Dual,
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "primal" => Ok(Algorithm::Primal),
+            "dual" => Ok(Algorithm::Dual),
+            _ => anyhow::bail!("unknown algorithm '{}', expected primal or dual", s),
+        }
+    }
+}
+
+/// How the simplex keeps the program in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// the dense tableau of `Simplex`, which supports every option, cuts
+    /// and re-optimization
+    Tableau,
+    /// `RevisedSimplex` on the sparse columns and a factorized basis, for
+    /// large programs; `BranchAndBound` uses it for programs without
+    /// integer variables
+    Revised,
+}
+
+impl std::str::FromStr for Method {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "tableau" => Ok(Method::Tableau),
+            "revised" => Ok(Method::Revised),
+            _ => anyhow::bail!("unknown method '{}', expected tableau or revised", s),
+        }
+    }
+}
+
+/// Knobs of the solver, `SolverOptions::default()` gives a two-phase simplex
+#[derive(Debug, Clone)]
+pub struct SolverOptions {
+    pub method: Method,
+    pub algorithm: Algorithm,
+    /// Starting basis of the primal simplex, unused by a dual start
+    pub init: InitStrategy,
+    /// Maximum number of pivots over all phases, None picks a limit from the tableau size
+    pub max_iter: Option<usize>,
+    /// Report Farkas multipliers for infeasible programs, which the tableau
+    /// solves Phase I once more for
+    pub farkas: bool,
+    /// Extract an irreducible infeasible subset of rows for infeasible
+    /// programs, needs `farkas`
+    pub iis: bool,
+    pub pivot_rule: PivotRule,
+    /// Fall back to Bland's rule after that many degenerate pivots in a row,
+    /// until the objective moves again. None never falls back.
+    pub bland_after: Option<usize>,
+    /// Record every tableau with its pivot in `Solution::trace`
+    pub trace: bool,
+}
+
+impl Default for SolverOptions {
+    fn default() -> Self {
+        Self {
+            method: Method::Tableau,
+            algorithm: Algorithm::Primal,
+            init: InitStrategy::TwoPhase,
+            max_iter: None,
+            farkas: true,
+            iis: true,
+            pivot_rule: PivotRule::Dantzig,
+            bland_after: Some(50),
+            trace: false,
+        }
+    }
+}
+
+/// Result of a pivoting loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Optimal,
+    /// the entering column is not bounded by any row
+    Unbounded(usize),
+    /// the dual simplex found a row that no pivot can make feasible
+    Infeasible(usize),
+    IterationLimit,
+}
+
+/// How far the entering variable can move, found by the ratio test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// pivot in the row, its basic variable leaves at zero
+    Pivot(usize),
+    /// pivot in the row, its basic variable leaves at its upper bound
+    PivotToUpper(usize),
+    /// the entering variable reaches its own upper bound before any basic
+    /// variable hits a bound, no pivot is needed
+    Flip,
+}
+
+/// Tableau columns of a variable, `x = offset + sum(sign * column)`
+#[derive(Debug, Clone)]
+struct VarCols {
+    offset: f64,
+    /// column and sign, two for a split free variable
+    cols: Vec<(usize, f64)>,
+}
+
+/// Role of a tableau column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColKind {
+    Objective,
+    Variable,
+    Slack,
+    Artificial,
+}
+
+/// Dense simplex tableau.
+///
+/// The tableau always maximizes, a `min` objective is stored negated.
+/// Row 0 holds the objective in the form `z - c^T x = 0`, i.e. the negated
+/// objective coefficients, rows 1.. hold one constraint each. Column 0 is the
+/// `z` column, followed by the variables, one slack (`<=`) or surplus (`>=`)
+/// column per inequality, one artificial column per row without a starting
+/// basic column and the right hand side as last column.
+///
+/// Bounds of the variables are not rows. Every variable column starts at
+/// zero: a finite lower bound `l` is shifted away (`x - l`), a variable with
+/// only an upper bound `u` is mirrored (`u - x`) and a free variable is split
+/// into two columns `x+ - x-`. The shift moves into the right hand side and
+/// a constant into the objective row. A nonbasic column then sits at zero or
+/// at its upper bound, in the latter case it stands for `u - x` instead of
+/// `x` (see `complement`), so every nonbasic column of the tableau is at zero
+/// and the usual pricing applies.
+#[derive(Clone)]
 pub struct Simplex {
     tableau: Array<f64, Ix2>,
-    symbolCol: HashMap<String, usize>,
+    var_cols: HashMap<String, VarCols>,
+    /// basic column of every constraint row, `basis[i]` belongs to tableau row `i + 1`
+    basis: Vec<usize>,
+    col_kind: Vec<ColKind>,
+    sense: Sense,
+    /// objective coefficient of every column in maximization form, negated
+    /// for complemented columns
+    cost: Vec<f64>,
+    /// upper bound of every column, infinite for slack and artificial columns
+    upper: Vec<f64>,
+    /// columns that stand for `upper - x`
+    at_upper: Vec<bool>,
+    /// starting basic column (slack or artificial) of every row, they form B^-1 in the tableau
+    init_basis: Vec<usize>,
+    /// slack or surplus column of every row, None for `=` rows
+    slack_of_row: Vec<Option<usize>>,
+    /// -1 for rows that were multiplied by -1 to get a non-negative right hand side
+    row_sign: Vec<f64>,
+    iterations: usize,
+    /// the program the tableau was built from
+    program: LinearProgram,
+    /// display name of every column: variables (`x+` and `x-` for split
+    /// ones), `s_<row>` for slack and surplus, `a_<row>` for artificial columns
+    col_names: Vec<String>,
+    trace: Option<Trace>,
 }
 
 impl Simplex {
-    fn from(value: AST) -> anyhow::Result<Self> {
-        let symbols = SymbolTable::from(value);
-        let n_symbols = symbols.table.len();
-        let mut symbolCol = HashMap::new();
-        let mut tableau = Array::zeros((
-            symbols.n_constr as usize + 1,
-            n_symbols + symbols.n_constr as usize - 1 + 2,
-        ));
-
-        let mut free_col = 1;
-        let max_col = symbols.n_constr as usize + n_symbols;
-        for (key, value_arr) in symbols.table.iter() {
-            let mut insert_col = free_col;
-
-            if key == "RHS" {
-                insert_col = max_col;
-            } else if symbolCol.contains_key(key) {
-                insert_col = symbolCol[key];
+    /// Lay out the tableau of a program
+    pub fn build(program: LinearProgram) -> Self {
+        let n_constr = program.n_constr();
+        let mut rhs = program.rhs.clone();
+        let mut cmp = program.cmp.clone();
+
+        // variable columns as program column and sign, with their upper bound
+        let mut structural: Vec<(usize, f64)> = vec![];
+        let mut col_upper = vec![];
+        let mut col_names = vec![String::from("z")];
+        let mut var_cols = HashMap::new();
+        let mut constant = program.obj_constant;
+        for (j, name) in program.names.iter().enumerate() {
+            let (lower, upper) = (program.lower[j], program.upper[j]);
+            let col = structural.len() + 1;
+            let (offset, cols) = if lower.is_finite() {
+                structural.push((j, 1.0));
+                col_upper.push(upper - lower);
+                col_names.push(name.clone());
+                (lower, vec![(col, 1.0)])
+            } else if upper.is_finite() {
+                structural.push((j, -1.0));
+                col_upper.push(f64::INFINITY);
+                col_names.push(name.clone());
+                (upper, vec![(col, -1.0)])
+            } else {
+                structural.extend([(j, 1.0), (j, -1.0)]);
+                col_upper.extend([f64::INFINITY; 2]);
+                col_names.extend([format!("{}+", name), format!("{}-", name)]);
+                (0.0, vec![(col, 1.0), (col + 1, -1.0)])
+            };
+            if offset != 0.0 {
+                for (row, a) in program.matrix.col(j) {
+                    rhs[row] -= a * offset;
+                }
+                constant += program.obj[j] * offset;
+            }
+            var_cols.insert(name.clone(), VarCols { offset, cols });
+        }
+        let n_vars = structural.len();
+
+        // normalize every row to a non-negative right hand side
+        let mut row_sign = vec![1.0; n_constr];
+        for row in 0..n_constr {
+            if rhs[row] < 0.0 {
+                row_sign[row] = -1.0;
+                rhs[row] = -rhs[row];
+                cmp[row] = match cmp[row] {
+                    CmpOperation::Leq => CmpOperation::Geq,
+                    CmpOperation::Geq => CmpOperation::Leq,
+                    _ => CmpOperation::Eq,
+                };
+            }
+        }
+
+        let n_slack = cmp.iter().filter(|c| **c != CmpOperation::Eq).count();
+        let n_art = cmp.iter().filter(|c| **c != CmpOperation::Leq).count();
+        let rhs_col = n_vars + n_slack + n_art + 1;
+        let mut tableau = Array::zeros((n_constr + 1, rhs_col + 1));
+        col_names.resize(rhs_col + 1, String::from("rhs"));
+        let mut col_kind = vec![ColKind::Objective];
+        col_kind.extend(vec![ColKind::Variable; n_vars]);
+        col_kind.extend(vec![ColKind::Slack; n_slack]);
+        col_kind.extend(vec![ColKind::Artificial; n_art]);
+
+        let sign = match program.sense {
+            Sense::Max => 1.0,
+            Sense::Min => -1.0,
+        };
+        let mut cost = vec![0.0; rhs_col];
+        let mut upper = vec![f64::INFINITY; rhs_col];
+        upper[1..=n_vars].copy_from_slice(&col_upper);
+        tableau[[0, 0]] = 1.0;
+        tableau[[0, rhs_col]] = sign * constant;
+        for (i, (j, col_sign)) in structural.iter().enumerate() {
+            cost[i + 1] = sign * col_sign * program.obj[*j];
+            tableau[[0, i + 1]] = -cost[i + 1];
+        }
+
+        let mut basis = Vec::with_capacity(n_constr);
+        let mut slack_of_row = vec![None; n_constr];
+        let mut slack_col = n_vars + 1;
+        let mut art_col = n_vars + n_slack + 1;
+        for (i, (j, col_sign)) in structural.iter().enumerate() {
+            for (row, a) in program.matrix.col(*j) {
+                tableau[[row + 1, i + 1]] = row_sign[row] * col_sign * a;
+            }
+        }
+        for row in 0..n_constr {
+            tableau[[row + 1, rhs_col]] = rhs[row];
+            match cmp[row] {
+                CmpOperation::Leq => {
+                    tableau[[row + 1, slack_col]] = 1.0;
+                    basis.push(slack_col);
+                }
+                CmpOperation::Geq => {
+                    tableau[[row + 1, slack_col]] = -1.0;
+                }
+                _ => {}
+            }
+            if cmp[row] != CmpOperation::Eq {
+                slack_of_row[row] = Some(slack_col);
+                col_names[slack_col] = format!("s_{}", row);
+                slack_col += 1;
+            }
+            if cmp[row] != CmpOperation::Leq {
+                col_names[art_col] = format!("a_{}", row);
+                tableau[[row + 1, art_col]] = 1.0;
+                basis.push(art_col);
+                art_col += 1;
+            }
+        }
+
+        Self {
+            tableau,
+            var_cols,
+            init_basis: basis.clone(),
+            basis,
+            col_kind,
+            sense: program.sense,
+            cost,
+            upper,
+            at_upper: vec![false; rhs_col],
+            slack_of_row,
+            row_sign,
+            iterations: 0,
+            program,
+            col_names,
+            trace: None,
+        }
+    }
+
+    fn rhs_col(&self) -> usize {
+        self.tableau.ncols() - 1
+    }
+
+    fn n_constr(&self) -> usize {
+        self.basis.len()
+    }
+
+    /// Entering column with a negative entry in the objective row chosen by
+    /// `rule`. Artificial columns never re-enter the basis.
+    fn entering_col(&self, obj_row: usize, rule: PivotRule) -> Option<usize> {
+        let candidates = (1..self.rhs_col()).filter(|col| {
+            self.col_kind[*col] != ColKind::Artificial && self.tableau[[obj_row, *col]] < -EPS
+        });
+        match rule {
+            PivotRule::Bland => candidates.min(),
+            PivotRule::Dantzig | PivotRule::Lexicographic => {
+                candidates.fold(None, |best: Option<usize>, col| match best {
+                    Some(b) if self.tableau[[obj_row, b]] <= self.tableau[[obj_row, col]] => best,
+                    _ => Some(col),
+                })
+            }
+            PivotRule::SteepestEdge => {
+                let score = |col: usize| {
+                    let d = self.tableau[[obj_row, col]];
+                    let norm: f64 = (1..=self.n_constr())
+                        .map(|r| self.tableau[[r, col]].powi(2))
+                        .sum();
+                    d * d / (1.0 + norm)
+                };
+                candidates
+                    .map(|col| (col, score(col)))
+                    .fold(None, |best: Option<(usize, f64)>, (col, v)| match best {
+                        Some((_, b)) if b >= v => best,
+                        _ => Some((col, v)),
+                    })
+                    .map(|(col, _)| col)
+            }
+        }
+    }
+
+    /// Bounded ratio test for the entering column: the step ends where a
+    /// basic variable reaches zero or its upper bound, or where the entering
+    /// variable reaches its own upper bound. Ties between rows are broken
+    /// according to `rule`. Returns the step with its length, None if the
+    /// column is not bounded at all.
+    fn leaving_row(&self, col: usize, rule: PivotRule) -> Option<(Step, f64)> {
+        let rhs_col = self.rhs_col();
+        let mut leaving: Option<(usize, bool)> = None;
+        let mut min_ratio = self.upper[col];
+        for row in 1..=self.n_constr() {
+            let a = self.tableau[[row, col]];
+            let rhs = self.tableau[[row, rhs_col]];
+            let upper = self.upper[self.basis[row - 1]];
+            let (ratio, to_upper) = if a > EPS {
+                (rhs / a, false)
+            } else if a < -EPS && upper.is_finite() {
+                ((upper - rhs) / -a, true)
+            } else {
+                continue;
+            };
+            if ratio < min_ratio - EPS {
+                min_ratio = ratio;
+                leaving = Some((row, to_upper));
+            } else if ratio <= min_ratio + EPS {
+                // a tie with the bound of the entering variable keeps the flip
+                let Some((current, current_to_upper)) = leaving else {
+                    continue;
+                };
+                let replace = match rule {
+                    PivotRule::Bland => self.basis[row - 1] < self.basis[current - 1],
+                    PivotRule::Lexicographic => {
+                        !to_upper && !current_to_upper && self.lex_less(row, current, col)
+                    }
+                    PivotRule::Dantzig | PivotRule::SteepestEdge => false,
+                };
+                if replace {
+                    leaving = Some((row, to_upper));
+                }
+            }
+        }
+        match leaving {
+            Some((row, false)) => Some((Step::Pivot(row), min_ratio)),
+            Some((row, true)) => Some((Step::PivotToUpper(row), min_ratio)),
+            None if min_ratio.is_finite() => Some((Step::Flip, min_ratio)),
+            None => None,
+        }
+    }
+
+    /// Compare the rows of `B^-1` scaled by their entry in `col` lexicographically
+    fn lex_less(&self, row: usize, other: usize, col: usize) -> bool {
+        let (a, b) = (self.tableau[[row, col]], self.tableau[[other, col]]);
+        for init_col in &self.init_basis {
+            let (u, v) = (
+                self.tableau[[row, *init_col]] / a,
+                self.tableau[[other, *init_col]] / b,
+            );
+            if (u - v).abs() > EPS {
+                return u < v;
+            }
+        }
+        false
+    }
+
+    /// Substitute `u - x` for the variable of a column with a finite upper
+    /// bound `u`, or back. Moves a nonbasic variable to its other bound.
+    fn complement(&mut self, col: usize) {
+        let upper = self.upper[col];
+        let rhs_col = self.rhs_col();
+        for i in 0..self.tableau.nrows() {
+            let a = self.tableau[[i, col]];
+            if a != 0.0 {
+                self.tableau[[i, rhs_col]] -= a * upper;
+                self.tableau[[i, col]] = -a;
+            }
+        }
+        self.cost[col] = -self.cost[col];
+        self.at_upper[col] = !self.at_upper[col];
+    }
+
+    /// Gauss-Jordan step on the pivot element, `col` becomes basic in `row`
+    fn pivot(&mut self, row: usize, col: usize) {
+        let pivot = self.tableau[[row, col]];
+        self.tableau.row_mut(row).mapv_inplace(|v| v / pivot);
+        let pivot_row = self.tableau.row(row).to_owned();
+        for i in 0..self.tableau.nrows() {
+            if i == row {
+                continue;
+            }
+            let factor = self.tableau[[i, col]];
+            if factor != 0.0 {
+                self.tableau.row_mut(i).scaled_add(-factor, &pivot_row);
+            }
+        }
+        self.basis[row - 1] = col;
+    }
+
+    /// Append the current tableau to the trace if one is recorded. `pivot`
+    /// holds the leaving column and the pivot element, an entering column
+    /// without a pivot moved to its other bound.
+    fn record(&mut self, obj_row: usize, entering: Option<usize>, pivot: Option<(usize, f64)>) {
+        if self.trace.is_none() {
+            return;
+        }
+        let mut row_labels = vec![String::from("z")];
+        row_labels.extend(self.basis.iter().map(|c| self.col_names[*c].clone()));
+        let (phase, objective) = if obj_row == 0 {
+            let sign = match self.sense {
+                Sense::Max => 1.0,
+                Sense::Min => -1.0,
+            };
+            // Big-M leaves its price on the artificial columns
+            let penalized = (1..self.rhs_col())
+                .any(|col| self.col_kind[col] == ColKind::Artificial && self.cost[col] != 0.0);
+            let phase = if penalized { Phase::BigM } else { Phase::Two };
+            (phase, sign * self.tableau[[0, self.rhs_col()]])
+        } else {
+            row_labels.push(String::from("w"));
+            (Phase::One, self.tableau[[obj_row, self.rhs_col()]])
+        };
+        let step = TraceStep {
+            phase,
+            entering: entering.map(|col| self.col_names[col].clone()),
+            leaving: pivot.map(|(leaving, _)| self.col_names[leaving].clone()),
+            pivot: pivot.map(|(_, value)| value),
+            objective,
+            row_labels,
+            tableau: self.tableau.clone(),
+        };
+        if let Some(trace) = self.trace.as_mut() {
+            trace.steps.push(step);
+        }
+    }
+
+    /// Pivot until the objective in `obj_row` can not be improved anymore or
+    /// the iteration limit is reached
+    fn iterate(&mut self, obj_row: usize, options: &SolverOptions) -> Outcome {
+        let max_iter = options
+            .max_iter
+            .unwrap_or(50 * self.tableau.nrows() * self.tableau.ncols());
+        let mut degenerate = 0;
+        self.record(obj_row, None, None);
+        loop {
+            let rule = match options.bland_after {
+                Some(n) if degenerate >= n => PivotRule::Bland,
+                _ => options.pivot_rule,
+            };
+            let Some(col) = self.entering_col(obj_row, rule) else {
+                return Outcome::Optimal;
+            };
+            if self.iterations >= max_iter {
+                return Outcome::IterationLimit;
+            }
+            let Some((step, ratio)) = self.leaving_row(col, rule) else {
+                return Outcome::Unbounded(col);
+            };
+            if ratio <= EPS {
+                degenerate += 1;
+            } else {
+                degenerate = 0;
+            }
+            let row = match step {
+                Step::Flip => {
+                    self.complement(col);
+                    self.iterations += 1;
+                    self.record(obj_row, Some(col), None);
+                    continue;
+                }
+                Step::Pivot(row) => row,
+                Step::PivotToUpper(row) => {
+                    // the basic variable leaves at its upper bound, which is
+                    // zero once it is complemented
+                    self.complement(self.basis[row - 1]);
+                    self.tableau.row_mut(row).mapv_inplace(|v| -v);
+                    row
+                }
+            };
+            let leaving = self.basis[row - 1];
+            let value = self.tableau[[row, col]];
+            self.pivot(row, col);
+            self.iterations += 1;
+            self.record(obj_row, Some(col), Some((leaving, value)));
+        }
+    }
+
+    /// Phase I: maximize the negated sum of the artificial variables in an
+    /// auxiliary objective row appended to the tableau. Afterwards every
+    /// artificial variable that is still basic (at zero) is pivoted out where
+    /// possible and the auxiliary row is dropped again.
+    fn phase_one(&mut self, options: &SolverOptions) -> Outcome {
+        let n_rows = self.tableau.nrows();
+        let mut aux = Array::zeros((n_rows + 1, self.tableau.ncols()));
+        aux.slice_mut(s![..n_rows, ..]).assign(&self.tableau);
+        for (i, col) in self.basis.iter().enumerate() {
+            if self.col_kind[*col] == ColKind::Artificial {
+                let row = self.tableau.row(i + 1).to_owned();
+                aux.row_mut(n_rows).scaled_add(-1.0, &row);
+                aux[[n_rows, *col]] = 0.0;
+            }
+        }
+        self.tableau = aux;
+
+        // artificials are excluded from entering, they only ever leave.
+        // The auxiliary objective is bounded by 0, so it can not be unbounded.
+        let outcome = self.iterate(n_rows, options);
+        let infeasible = self.tableau[[n_rows, self.rhs_col()]] < -FEAS_EPS;
+        if outcome != Outcome::Optimal || infeasible {
+            self.tableau = self.tableau.slice(s![..n_rows, ..]).to_owned();
+            return outcome;
+        }
+
+        self.drive_out_artificials();
+        self.tableau = self.tableau.slice(s![..n_rows, ..]).to_owned();
+        Outcome::Optimal
+    }
+
+    /// Pivot every artificial variable that is basic at zero out of the basis
+    /// where possible. Redundant rows keep their artificial, which loses its
+    /// price so it does not show up in the duals. True if any pivot was made.
+    fn drive_out_artificials(&mut self) -> bool {
+        let mut pivoted = false;
+        for row in 1..=self.n_constr() {
+            let basic = self.basis[row - 1];
+            if self.col_kind[basic] != ColKind::Artificial {
+                continue;
+            }
+            match (1..self.rhs_col()).find(|c| {
+                self.col_kind[*c] != ColKind::Artificial && self.tableau[[row, *c]].abs() > EPS
+            }) {
+                Some(col) => {
+                    self.pivot(row, col);
+                    pivoted = true;
+                }
+                None => {
+                    let row = self.tableau.row(row).to_owned();
+                    self.tableau.row_mut(0).scaled_add(-self.cost[basic], &row);
+                    self.tableau[[0, basic]] = 0.0;
+                    self.cost[basic] = 0.0;
+                }
+            }
+        }
+        pivoted
+    }
+
+    /// Big-M: price every artificial column with `-m` and eliminate the basic
+    /// artificials from the objective row. Infeasibility shows up as an
+    /// artificial variable that is still basic at a positive level at the optimum.
+    fn big_m(&mut self, m: f64) {
+        for col in 1..self.rhs_col() {
+            if self.col_kind[col] == ColKind::Artificial {
+                self.cost[col] = -m;
+                self.tableau[[0, col]] = m;
+            }
+        }
+        for (i, col) in self.basis.iter().enumerate() {
+            if self.col_kind[*col] == ColKind::Artificial {
+                let row = self.tableau.row(i + 1).to_owned();
+                self.tableau.row_mut(0).scaled_add(-m, &row);
+            }
+        }
+    }
+
+    /// Append the row `coefs^T x (cmp) rhs` over the program variables to a
+    /// solved tableau. The row is written in the current basis with a new
+    /// slack column as its basic variable, which is negative if the current
+    /// solution violates the row; `reoptimize` takes it from there. The row
+    /// is added to the program as well, so it shows up in the solution.
+    pub fn add_row(
+        &mut self,
+        name: Option<String>,
+        coefs: Vec<f64>,
+        cmp: CmpOperation,
+        rhs: f64,
+    ) -> anyhow::Result<()> {
+        let row_sign = match cmp {
+            CmpOperation::Leq => 1.0,
+            CmpOperation::Geq => -1.0,
+            _ => anyhow::bail!("only '<=' and '>=' rows can be added to a solved tableau"),
+        };
+        if coefs.len() != self.program.names.len() {
+            anyhow::bail!(
+                "row has {} coefficients for {} variables",
+                coefs.len(),
+                self.program.names.len()
+            );
+        }
+
+        // the row over the tableau columns, each standing for an offset plus
+        // a signed column, or the upper bound minus it if complemented
+        let rhs_col = self.rhs_col();
+        let mut row = Array::zeros(rhs_col + 2);
+        let mut constant = 0.0;
+        for (name, c) in self.program.names.iter().zip(&coefs) {
+            let var = &self.var_cols[name];
+            constant += c * var.offset;
+            for (col, sign) in &var.cols {
+                row[*col] += c * sign * self.col_sign(*col);
+                if self.at_upper[*col] {
+                    constant += c * sign * self.upper[*col];
+                }
+            }
+        }
+        row[rhs_col + 1] = rhs - constant;
+        row.mapv_inplace(|v| row_sign * v);
+        // the new slack column sits right before the right hand side
+        row[rhs_col] = 1.0;
+        // eliminate the basic columns
+        for (i, col) in self.basis.iter().enumerate() {
+            let factor = row[*col];
+            if factor != 0.0 {
+                let basic_row = self.tableau.row(i + 1);
+                for (j, value) in basic_row.iter().enumerate() {
+                    let j = if j == rhs_col { rhs_col + 1 } else { j };
+                    row[j] -= factor * value;
+                }
+            }
+        }
+
+        let (n_rows, n_cols) = self.tableau.dim();
+        let mut tableau = Array::zeros((n_rows + 1, n_cols + 1));
+        tableau
+            .slice_mut(s![..n_rows, ..rhs_col])
+            .assign(&self.tableau.slice(s![.., ..rhs_col]));
+        tableau
+            .slice_mut(s![..n_rows, rhs_col + 1])
+            .assign(&self.tableau.column(rhs_col));
+        tableau.row_mut(n_rows).assign(&row);
+        self.tableau = tableau;
+
+        let program_row = self.program.n_constr();
+        self.basis.push(rhs_col);
+        self.init_basis.push(rhs_col);
+        self.col_kind.push(ColKind::Slack);
+        self.cost.push(0.0);
+        self.upper.push(f64::INFINITY);
+        self.at_upper.push(false);
+        self.col_names.insert(rhs_col, format!("s_{}", program_row));
+        self.slack_of_row.push(Some(rhs_col));
+        self.row_sign.push(row_sign);
+        self.program.matrix.push_row(coefs.into_iter().enumerate());
+        self.program.rhs.push(rhs);
+        self.program.cmp.push(cmp);
+        self.program.row_names.push(name);
+        Ok(())
+    }
+
+    /// Move a solved tableau to new bounds `[lower, upper]` of the variable
+    /// at `var`. Its columns are shifted to the new bound they are anchored at
+    /// and their upper bounds change, which moves the basic values but keeps
+    /// every reduced cost, so the basis stays dual feasible and `reoptimize`
+    /// takes it from there. The pair of a split free variable is anchored at
+    /// the bound closest to zero, each column spans the box on its side.
+    pub fn set_bounds(&mut self, var: usize, lower: f64, upper: f64) -> anyhow::Result<()> {
+        let name = self.program.names[var].clone();
+        if lower > upper {
+            anyhow::bail!(
+                "variable '{}' has no value within its bounds [{}, {}]",
+                name,
+                lower,
+                upper
+            );
+        }
+
+        // x = offset + sum(sign * y), the new columns y' = y - delta start at the new offset
+        let cols = self.var_cols[&name].cols.clone();
+        let offset = match cols[..] {
+            [(_, sign)] if sign > 0.0 => lower,
+            [_] => upper,
+            _ => 0.0_f64.max(lower).min(upper),
+        };
+        if !offset.is_finite() {
+            anyhow::bail!(
+                "variable '{}' can not drop the bound its column starts at",
+                name
+            );
+        }
+        let complemented = cols
+            .iter()
+            .map(|(col, _)| self.at_upper[*col])
+            .collect::<Vec<_>>();
+        for ((col, _), complemented) in cols.iter().zip(&complemented) {
+            if *complemented {
+                self.complement(*col);
+            }
+        }
+        // the columns of a split pair are negated copies, shifting the first moves both
+        let (col, sign) = cols[0];
+        let delta = sign * (offset - self.var_cols[&name].offset);
+        if delta != 0.0 {
+            let rhs_col = self.rhs_col();
+            for i in 0..self.tableau.nrows() {
+                let a = self.tableau[[i, col]];
+                self.tableau[[i, rhs_col]] -= a * delta;
+            }
+        }
+        for ((col, sign), complemented) in cols.iter().zip(complemented) {
+            self.upper[*col] = if *sign > 0.0 {
+                upper - offset
             } else {
-                symbolCol.insert(key.clone(), insert_col);
-                free_col += 1;
+                offset - lower
+            };
+            if complemented && self.upper[*col].is_finite() {
+                self.complement(*col);
+            }
+        }
+        if let Some(var_cols) = self.var_cols.get_mut(&name) {
+            var_cols.offset = offset;
+        }
+        self.program.lower[var] = lower;
+        self.program.upper[var] = upper;
+        Ok(())
+    }
+
+    /// Prepare a freshly built tableau for the dual simplex. The starting
+    /// basis of slack and artificial columns has the cost vector as its
+    /// reduced costs; columns pricing out wrong move to their upper bound.
+    /// An artificial column gets the upper bound zero, which makes it the
+    /// slack of its row fixed at zero. False, and nothing changes, if a
+    /// column prices out wrong and has no upper bound.
+    fn dual_start(&mut self) -> bool {
+        let wrong = (1..self.rhs_col())
+            .filter(|col| {
+                self.col_kind[*col] != ColKind::Artificial && self.tableau[[0, *col]] < -EPS
+            })
+            .collect::<Vec<_>>();
+        if wrong.iter().any(|col| self.upper[*col].is_infinite()) {
+            return false;
+        }
+        for col in wrong {
+            self.complement(col);
+        }
+        for col in 1..self.rhs_col() {
+            if self.col_kind[col] == ColKind::Artificial {
+                self.upper[col] = 0.0;
+            }
+        }
+        true
+    }
+
+    /// Dual simplex on a dual feasible basis: the row whose basic variable is
+    /// farthest outside its bounds leaves, the entering column keeps every
+    /// reduced cost non-negative. A basic variable above its upper bound is
+    /// complemented first, so that it leaves at zero.
+    fn dual_iterate(&mut self, options: &SolverOptions) -> Outcome {
+        let max_iter = options
+            .max_iter
+            .unwrap_or(50 * self.tableau.nrows() * self.tableau.ncols());
+        let rhs_col = self.rhs_col();
+        self.record(0, None, None);
+        loop {
+            let mut leaving: Option<(usize, f64)> = None;
+            for row in 1..=self.n_constr() {
+                let value = self.tableau[[row, rhs_col]];
+                let upper = self.upper[self.basis[row - 1]];
+                let infeasibility = (-value).max(value - upper);
+                if infeasibility > FEAS_EPS && leaving.is_none_or(|(_, v)| infeasibility > v) {
+                    leaving = Some((row, infeasibility));
+                }
+            }
+            let Some((row, _)) = leaving else {
+                return Outcome::Optimal;
+            };
+            if self.iterations >= max_iter {
+                return Outcome::IterationLimit;
+            }
+            if self.tableau[[row, rhs_col]] > 0.0 {
+                self.complement(self.basis[row - 1]);
+                self.tableau.row_mut(row).mapv_inplace(|v| -v);
+            }
+
+            // smallest ratio of reduced cost to the negative row entry, ties
+            // go to the larger entry
+            let mut entering: Option<(usize, f64)> = None;
+            for col in 1..rhs_col {
+                let a = self.tableau[[row, col]];
+                if self.col_kind[col] == ColKind::Artificial || a >= -EPS {
+                    continue;
+                }
+                let ratio = self.tableau[[0, col]].max(0.0) / -a;
+                let better = match entering {
+                    None => true,
+                    Some((best, best_ratio)) => {
+                        ratio < best_ratio - EPS
+                            || (ratio <= best_ratio + EPS && a < self.tableau[[row, best]])
+                    }
+                };
+                if better {
+                    entering = Some((col, ratio));
+                }
+            }
+            let Some((col, _)) = entering else {
+                return Outcome::Infeasible(row);
+            };
+            let leaving = self.basis[row - 1];
+            let value = self.tableau[[row, col]];
+            self.pivot(row, col);
+            self.iterations += 1;
+            self.record(0, Some(col), Some((leaving, value)));
+        }
+    }
+
+    /// Gomory mixed-integer cuts from the rows of an optimal tableau whose
+    /// basic variable is integer but has a fractional value `f0`. With all
+    /// nonbasic columns at zero, the row `x_B + sum a_j x_j = b` gives the cut
+    /// `sum_{integer j} min(f_j / f0, (1 - f_j) / (1 - f0)) x_j
+    ///  + sum_{continuous j} max(a_j / f0, -a_j / (1 - f0)) x_j >= 1`
+    /// with `f_j` the fractional part of `a_j`. On pure integer rows this is
+    /// the fractional cut `sum f_j x_j >= f0`, strengthened.
+    ///
+    /// A column counts as integer if it belongs to an integer variable and
+    /// its shift and upper bound are integer, slack columns are continuous.
+    /// Every cut is returned as `coefs^T x >= rhs` over the program
+    /// variables, ready for `add_row`. Cuts that involve a split free
+    /// variable, which has no linear form in `x`, are skipped.
+    pub fn gomory_cuts(&self) -> Vec<(Vec<f64>, f64)> {
+        let rhs_col = self.rhs_col();
+        let n_vars = self.program.names.len();
+        // variable, sign and offset of every column of a variable
+        let mut col_var: Vec<Option<(usize, f64, f64, bool)>> = vec![None; rhs_col];
+        for (k, name) in self.program.names.iter().enumerate() {
+            let var = &self.var_cols[name];
+            let split = var.cols.len() > 1;
+            for (col, sign) in &var.cols {
+                col_var[*col] = Some((k, *sign, var.offset, split));
+            }
+        }
+        let mut row_of_slack = vec![None; rhs_col];
+        for (row, col) in self.slack_of_row.iter().enumerate() {
+            if let Some(col) = col {
+                row_of_slack[*col] = Some(row);
+            }
+        }
+        let is_integral = |v: f64| (v - v.round()).abs() < EPS;
+        let col_integer = |col: usize| match col_var[col] {
+            Some((k, _, offset, false)) => {
+                self.program.integer[k]
+                    && is_integral(offset)
+                    && (!self.at_upper[col] || is_integral(self.upper[col]))
+            }
+            _ => false,
+        };
+        let is_basic = {
+            let mut is_basic = vec![false; rhs_col];
+            self.basis.iter().for_each(|b| is_basic[*b] = true);
+            is_basic
+        };
+
+        let mut cuts = vec![];
+        'rows: for (i, b) in self.basis.iter().enumerate() {
+            let value = self.tableau[[i + 1, rhs_col]];
+            let f0 = value - value.floor();
+            if !col_integer(*b) || !(GOMORY_MIN_FRAC..=1.0 - GOMORY_MIN_FRAC).contains(&f0) {
+                continue;
             }
-            for value in value_arr {
-                match value {
-                    Symbol::Obj(value) => {
-                        tableau[[0, insert_col]] = value.0;
+            // the cut over the program variables: each nonbasic column is an
+            // affine function `g^T x + h` of them
+            let mut coefs = vec![0.0; n_vars];
+            let mut rhs = 1.0;
+            for col in 1..rhs_col {
+                let a = self.tableau[[i + 1, col]];
+                if is_basic[col] || self.col_kind[col] == ColKind::Artificial || a.abs() < EPS {
+                    continue;
+                }
+                let c = if col_integer(col) {
+                    let f = a - a.floor();
+                    if f <= f0 {
+                        f / f0
+                    } else {
+                        (1.0 - f) / (1.0 - f0)
                     }
-                    Symbol::Constr(row, value) => {
-                        tableau[[*row as usize + 1, insert_col]] = value.0;
+                } else if a >= 0.0 {
+                    a / f0
+                } else {
+                    -a / (1.0 - f0)
+                };
+                if c == 0.0 {
+                    continue;
+                }
+                if c > GOMORY_MAX_COEF {
+                    continue 'rows;
+                }
+                if let Some((k, sign, offset, split)) = col_var[col] {
+                    if split {
+                        continue 'rows;
                     }
-                    Symbol::RHS(row, value) => {
-                        tableau[[*row as usize + 1, insert_col]] = value.0;
+                    // y = sign (x - offset), the column is y or upper - y
+                    let (g, h) = if self.at_upper[col] {
+                        (-sign, self.upper[col] + sign * offset)
+                    } else {
+                        (sign, -sign * offset)
+                    };
+                    coefs[k] += c * g;
+                    rhs -= c * h;
+                } else if let Some(row) = row_of_slack[col] {
+                    // the slack is the distance to the right hand side
+                    let (g, h) = match self.program.cmp[row] {
+                        CmpOperation::Geq => (1.0, -self.program.rhs[row]),
+                        _ => (-1.0, self.program.rhs[row]),
+                    };
+                    for (k, a) in self.program.matrix.row(row) {
+                        coefs[k] += c * g * a;
                     }
+                    rhs -= c * h;
+                }
+            }
+            if coefs.iter().any(|c| c.abs() > EPS) {
+                cuts.push((coefs, rhs));
+            }
+        }
+        cuts
+    }
+
+    /// Value of every column of the tableau in the current basic solution,
+    /// complemented columns are turned back into their variable
+    fn column_values(&self) -> Vec<f64> {
+        let rhs_col = self.rhs_col();
+        let mut values = vec![0.0; rhs_col];
+        for (i, col) in self.basis.iter().enumerate() {
+            values[*col] = self.tableau[[i + 1, rhs_col]];
+        }
+        for (col, value) in values.iter_mut().enumerate() {
+            if self.at_upper[col] {
+                *value = self.upper[col] - *value;
+            }
+        }
+        values
+    }
+
+    /// +1 for plain columns, -1 for complemented ones
+    fn col_sign(&self, col: usize) -> f64 {
+        if self.at_upper[col] {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// True if an artificial variable is basic at a positive level
+    fn artificial_in_basis(&self) -> bool {
+        let rhs_col = self.rhs_col();
+        self.basis.iter().enumerate().any(|(i, col)| {
+            self.col_kind[*col] == ColKind::Artificial && self.tableau[[i + 1, rhs_col]] > FEAS_EPS
+        })
+    }
+
+    /// Dual prices `y = c_B B^-1` of the normalized rows in maximization form.
+    /// The columns of the starting basis hold `B^-1` in the tableau.
+    fn row_duals(&self) -> Vec<f64> {
+        self.init_basis
+            .iter()
+            .map(|init_col| {
+                self.basis
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| self.cost[*col] * self.tableau[[i + 1, *init_col]])
+                    .sum::<f64>()
+                    // a dual start may leave a fixed artificial column complemented
+                    * self.col_sign(*init_col)
+            })
+            .collect()
+    }
+
+    /// Whether Phase I from scratch finds a feasible basis
+    fn feasible(program: &LinearProgram) -> bool {
+        let mut simplex = Self::build(program.clone());
+        simplex.phase_one(&SolverOptions::default()) != Outcome::Optimal
+            || !simplex.artificial_in_basis()
+    }
+
+    /// Farkas multipliers of the rows of an infeasible program, read from the
+    /// Phase I objective `max -sum(a)` as `y = c_B B^-1`. None if the program
+    /// turns out feasible.
+    fn farkas(program: &LinearProgram) -> Option<Vec<f64>> {
+        let mut simplex = Self::build(program.clone());
+        if !simplex.col_kind.contains(&ColKind::Artificial) {
+            return None;
+        }
+        let options = SolverOptions::default();
+        if simplex.phase_one(&options) != Outcome::Optimal || !simplex.artificial_in_basis() {
+            return None;
+        }
+        let y = simplex
+            .init_basis
+            .iter()
+            .zip(&simplex.row_sign)
+            .map(|(init_col, row_sign)| {
+                let y: f64 = simplex
+                    .basis
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, col)| simplex.col_kind[**col] == ColKind::Artificial)
+                    .map(|(i, _)| -simplex.tableau[[i + 1, *init_col]])
+                    .sum();
+                row_sign * y
+            })
+            .collect();
+        Some(y)
+    }
+
+    /// Irreducible infeasible subset by a deletion filter. Starts from the rows
+    /// used by the Farkas certificate and drops every row whose removal keeps
+    /// the remaining rows infeasible, shrinking to the support of the new
+    /// certificate on the way. `certificate` finds the Farkas multipliers of
+    /// a subset of the rows, None if they are feasible.
+    pub fn iis(
+        program: &LinearProgram,
+        farkas: &[f64],
+        certificate: impl Fn(&LinearProgram) -> Option<Vec<f64>>,
+    ) -> Vec<usize> {
+        let mut rows = (0..program.n_constr())
+            .filter(|r| farkas[*r].abs() > EPS)
+            .collect::<Vec<_>>();
+        let mut necessary = vec![];
+        while let Some(row) = rows.iter().find(|r| !necessary.contains(*r)).copied() {
+            let candidate = rows
+                .iter()
+                .filter(|r| **r != row)
+                .copied()
+                .collect::<Vec<_>>();
+            match certificate(&program.subset(&candidate)) {
+                Some(y) => {
+                    rows = candidate
+                        .iter()
+                        .zip(&y)
+                        .filter(|(_, y)| y.abs() > EPS)
+                        .map(|(r, _)| *r)
+                        .collect();
                 }
+                None => necessary.push(row),
             }
         }
-        for i in free_col..max_col {
-            symbolCol.insert(format!("sub_v_{}", i), i);
+        rows
+    }
+
+    /// Solution of an infeasible program with its certificate
+    fn infeasible_solution(&self, options: &SolverOptions) -> Solution {
+        let mut solution = Solution::empty(Status::Infeasible, self.iterations);
+        if !options.farkas {
+            return solution;
+        }
+        solution.farkas = Self::farkas(&self.program);
+        if let (true, Some(farkas)) = (options.iis, &solution.farkas) {
+            solution.iis = Some(Self::iis(&self.program, farkas, Self::farkas));
+        }
+        solution
+    }
+
+    /// Collect the current basic solution into a `Solution` with the given status
+    fn solution(&self, status: Status) -> Solution {
+        let sign = match self.sense {
+            Sense::Max => 1.0,
+            Sense::Min => -1.0,
+        };
+        let values = self.column_values();
+
+        let objective = match status {
+            Status::Unbounded => sign * f64::INFINITY,
+            _ => sign * self.tableau[[0, self.rhs_col()]],
+        };
+        let slacks = self
+            .slack_of_row
+            .iter()
+            .map(|col| col.map(|c| values[c]).unwrap_or(0.0))
+            .collect();
+        let duals = self
+            .row_duals()
+            .iter()
+            .zip(&self.row_sign)
+            .map(|(y, row_sign)| sign * row_sign * y)
+            .collect();
+        // the columns of a split variable have opposite reduced costs
+        let reduced_costs = self
+            .var_cols
+            .iter()
+            .map(|(name, var)| {
+                let (col, col_sign) = var.cols[0];
+                let z: f64 = self
+                    .basis
+                    .iter()
+                    .enumerate()
+                    .map(|(i, b)| self.cost[*b] * self.tableau[[i + 1, col]])
+                    .sum();
+                (
+                    name.clone(),
+                    sign * col_sign * self.col_sign(col) * (self.cost[col] - z),
+                )
+            })
+            .collect();
+
+        Solution {
+            status,
+            objective,
+            values: self
+                .var_cols
+                .iter()
+                .map(|(name, var)| {
+                    let value = var.cols.iter().map(|(c, s)| s * values[*c]).sum::<f64>();
+                    (name.clone(), var.offset + value)
+                })
+                .collect(),
+            slacks,
+            duals,
+            reduced_costs,
+            iterations: self.iterations,
+            ray: None,
+            farkas: None,
+            iis: None,
+            trace: None,
+            row_names: vec![],
+            nodes: None,
+            bound: None,
+        }
+    }
+
+    /// Extreme ray along the unbounded entering column `col`: the entering
+    /// variable grows by 1 and every basic variable changes by the negated
+    /// tableau entry of its row, in the opposite direction if complemented.
+    fn ray(&self, col: usize) -> HashMap<String, f64> {
+        let mut direction = vec![0.0; self.rhs_col()];
+        direction[col] = self.col_sign(col);
+        for (i, b) in self.basis.iter().enumerate() {
+            direction[*b] = -self.col_sign(*b) * self.tableau[[i + 1, col]];
+        }
+        self.var_cols
+            .iter()
+            .map(|(name, var)| {
+                let d = var.cols.iter().map(|(c, s)| s * direction[*c]).sum();
+                (name.clone(), d)
+            })
+            .collect()
+    }
+
+    /// Solve the program. The starting basis for `>=` and `=` rows is found
+    /// according to `options.init`, nothing extra happens if every row has a slack.
+    pub fn solve_with(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        if options.trace {
+            self.trace = Some(Trace {
+                columns: self.col_names.clone(),
+                steps: vec![],
+            });
+        }
+        let mut solution = self.run(options)?;
+        solution.trace = self.trace.take();
+        solution.row_names = (0..self.program.n_constr())
+            .map(|row| self.program.row_label(row))
+            .collect();
+        Ok(solution)
+    }
+
+    /// Solve again after `add_row` or `set_bounds`: the dual simplex restores
+    /// feasibility from the current basis, which stays dual feasible, and the
+    /// primal simplex cleans up. Reports the same way as `solve_with`, the
+    /// iterations count the pivots of this call only.
+    pub fn reoptimize(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        if options.trace {
+            self.trace = Some(Trace {
+                columns: self.col_names.clone(),
+                steps: vec![],
+            });
+        }
+        self.iterations = 0;
+        let mut solution = self.dual(options)?;
+        solution.trace = self.trace.take();
+        solution.row_names = (0..self.program.n_constr())
+            .map(|row| self.program.row_label(row))
+            .collect();
+        Ok(solution)
+    }
+
+    fn run(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        if options.algorithm == Algorithm::Dual && self.dual_start() {
+            return self.dual(options);
+        }
+        if self.col_kind.contains(&ColKind::Artificial) {
+            match options.init {
+                InitStrategy::TwoPhase => match self.phase_one(options) {
+                    Outcome::Optimal if self.artificial_in_basis() => {
+                        return Ok(self.infeasible_solution(options))
+                    }
+                    Outcome::Optimal => {}
+                    Outcome::IterationLimit => {
+                        return Ok(Solution::empty(Status::IterationLimit, self.iterations))
+                    }
+                    Outcome::Unbounded(_) | Outcome::Infeasible(_) => {
+                        anyhow::bail!("phase I objective is bounded but reported unbounded")
+                    }
+                },
+                InitStrategy::BigM(m) => self.big_m(m),
+            }
         }
+        self.primal(options)
+    }
 
-        println!("{:?}, {:?}", tableau, symbolCol);
+    /// Dual simplex from a dual feasible basis, then Phase II for whatever
+    /// the dual ratio test left behind
+    fn dual(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        match self.dual_iterate(options) {
+            Outcome::Optimal => self.primal(options),
+            Outcome::Infeasible(_) => Ok(self.infeasible_solution(options)),
+            Outcome::IterationLimit => Ok(Solution::empty(Status::IterationLimit, self.iterations)),
+            Outcome::Unbounded(_) => anyhow::bail!("dual simplex reported an unbounded column"),
+        }
+    }
 
-        Ok(Self { tableau, symbolCol })
+    /// Phase II from a feasible basis
+    fn primal(&mut self, options: &SolverOptions) -> anyhow::Result<Solution> {
+        let status = match self.iterate(0, options) {
+            Outcome::Optimal => Status::Optimal,
+            Outcome::Unbounded(_)
+                if self.artificial_in_basis() && !Self::feasible(&self.program) =>
+            {
+                // Big-M can run into a ray before it drives the artificials out
+                return Ok(self.infeasible_solution(options));
+            }
+            Outcome::Unbounded(col) => {
+                let mut solution = self.solution(Status::Unbounded);
+                solution.ray = Some(self.ray(col));
+                return Ok(solution);
+            }
+            Outcome::IterationLimit => Status::IterationLimit,
+            Outcome::Infeasible(_) => anyhow::bail!("primal simplex reported an infeasible row"),
+        };
+        // with Big-M an artificial may stay basic at the optimum
+        if status == Status::Optimal && self.artificial_in_basis() {
+            return Ok(self.infeasible_solution(options));
+        }
+        // or basic at zero, the degenerate pivots that take it out may call
+        // for more iterations
+        if status == Status::Optimal && self.drive_out_artificials() {
+            return self.primal(options);
+        }
+        Ok(self.solution(status))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{parser::ast::construct_ast, semantics::simplex::Simplex};
+    use crate::{
+        lexer::tokens::CmpOperation,
+        parser::ast::{construct_ast, AST},
+        semantics::{
+            program::LinearProgram,
+            simplex::{Algorithm, ColKind, InitStrategy, PivotRule, Simplex, SolverOptions},
+            solution::Status,
+            symbols::{Sense, SymbolTable},
+            trace::Phase,
+        },
+        test_util::{assert_close, dense_row, from_dense},
+    };
+
+    /// Tableau of a parsed program, with its bounds checked
+    fn tableau(ast: AST) -> anyhow::Result<Simplex> {
+        let program = LinearProgram::try_from(&SymbolTable::from(ast))?;
+        program.check_bounds()?;
+        Ok(Simplex::build(program))
+    }
 
     #[test]
     fn test_simplex() {
         {
             let input = "
-max {x1 - x2 }
+max {9x_1 + 16x_2 + 10x_3 }
 st {
-    -1.21x1 >= 1000
-    x2 >= 1000
+    5x_1 + 4x_2 + 2x_3 <= 1100
+    4x_1 + 8x_2 + x_3 <= 1600
+    x_3 <= 500
 }
 ";
             let ast = construct_ast(input.as_bytes());
             assert!(ast.is_ok());
             let ast = ast.unwrap();
-            let simplex = Simplex::from(ast).unwrap();
-            panic!()
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 5400.0);
+            assert_close(sol.values["x_1"], 0.0);
+            assert_close(sol.values["x_2"], 25.0);
+            assert_close(sol.values["x_3"], 500.0);
+        }
+        {
+            let input = "
+max {3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 36.0);
+            assert_close(sol.values["x1"], 2.0);
+            assert_close(sol.values["x2"], 6.0);
+        }
+    }
+
+    #[test]
+    fn test_simplex_unbounded() {
+        {
+            let input = "
+max {x1 + x2 }
+st {
+    x1 - x2 <= 10
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Unbounded);
+            assert_eq!(sol.objective, f64::INFINITY);
+            let ray = sol.ray.unwrap();
+            assert_close(ray["x1"], 1.0);
+            assert_close(ray["x2"], 1.0);
+        }
+        {
+            let input = "
+min {-x1 + x2 + x3 }
+st {
+    x1 - x2 >= 1
+    x2 + x3 <= 5
+    x3 >= 1
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, Status::Unbounded);
+            assert_eq!(sol.objective, f64::NEG_INFINITY);
+            let ray = sol.ray.unwrap();
+            // improving for min and keeps every row feasible
+            assert!(-ray["x1"] + ray["x2"] + ray["x3"] < 0.0);
+            assert!(ray["x1"] - ray["x2"] >= -1e-9);
+            assert!(ray["x2"] + ray["x3"] <= 1e-9);
+            assert!(ray["x3"].abs() <= 1e-9);
+            assert!(ray.values().all(|v| *v >= -1e-9));
+        }
+    }
+
+    #[test]
+    fn test_two_phase() {
+        {
+            let input = "
+max {-2x1 - 3x2 }
+st {
+    x1 + x2 >= 4
+    x1 + 3x2 >= 6
+    x1 - x2 = 0
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, -10.0);
+            assert_close(sol.values["x1"], 2.0);
+            assert_close(sol.values["x2"], 2.0);
+        }
+        {
+            let input = "
+max {2x + 3y }
+st {
+    x + y <= 4
+    x + 3y >= 6
+    x = 1
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 11.0);
+            assert_close(sol.values["x"], 1.0);
+            assert_close(sol.values["y"], 3.0);
+        }
+        {
+            // negative right hand side turns the row around
+            let input = "
+max {x1 + x2 }
+st {
+    -x1 - x2 >= -5
+    x1 <= 3
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_close(sol.objective, 5.0);
+        }
+    }
+
+    #[test]
+    fn test_two_phase_infeasible() {
+        {
+            let input = "
+max {x1 - x2 }
+st {
+    -1.21x1 >= 1000
+    x2 >= 1000
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            assert_eq!(
+                simplex.solve_with(&Default::default()).unwrap().status,
+                Status::Infeasible
+            );
+        }
+        {
+            let input = "
+max {x1 + x2 }
+st {
+    x1 + x2 <= 2
+    x1 + x2 >= 3
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            assert_eq!(
+                simplex.solve_with(&Default::default()).unwrap().status,
+                Status::Infeasible
+            );
+        }
+    }
+
+    #[test]
+    fn test_big_m() {
+        let options = SolverOptions {
+            init: InitStrategy::BigM(1000.0),
+            ..Default::default()
+        };
+        {
+            let input = "
+max {-2x1 - 3x2 }
+st {
+    x1 + x2 >= 4
+    x1 + 3x2 >= 6
+    x1 - x2 = 0
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&options).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, -10.0);
+            assert_close(sol.values["x1"], 2.0);
+            assert_close(sol.values["x2"], 2.0);
+        }
+        {
+            let input = "
+max {2x + 3y }
+st {
+    x + y <= 4
+    x + 3y >= 6
+    x = 1
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&options).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 11.0);
+            assert_close(sol.values["x"], 1.0);
+            assert_close(sol.values["y"], 3.0);
+        }
+        {
+            // the artificial of the first row is basic at zero at the optimum
+            let input = "
+max {x1 }
+st {
+    -x0 - x1 = 0
+    x0 - x1 <= 2
+    x0 + x1 >= 0
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast).unwrap().solve_with(&options).unwrap();
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let two_phase = tableau(ast)
+                .unwrap()
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 0.0);
+            for (dual, expected) in sol.duals.iter().zip(&two_phase.duals) {
+                assert_close(*dual, *expected);
+            }
+            for (name, reduced_cost) in &two_phase.reduced_costs {
+                assert_close(sol.reduced_costs[name], *reduced_cost);
+            }
+        }
+        {
+            let input = "
+max {x1 + x2 }
+st {
+    x1 + x2 <= 2
+    x1 + x2 >= 3
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            assert_eq!(
+                simplex.solve_with(&options).unwrap().status,
+                Status::Infeasible
+            );
+        }
+        {
+            // the penalized program is unbounded before the artificial leaves
+            let input = "
+max {4x0 + 6x1 + 7x2 + 5x3 + 3x4 }
+st {
+    0x2 - 3x4 = 14
+}
+bounds {
+    x0 <= 4
+    x1 >= -3
+    0 <= x2 <= 8
+    x4 >= -2
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            assert_eq!(
+                simplex.solve_with(&options).unwrap().status,
+                Status::Infeasible
+            );
+        }
+    }
+
+    #[test]
+    fn test_tableau_columns_per_relation() {
+        {
+            let input = "
+max {x1 + x2 }
+st {
+    x1 + x2 <= 4
+    x1 >= 1
+    x2 = 2
+    x1 - x2 >= -3
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let simplex = tableau(ast).unwrap();
+            // z, x1, x2, three slack/surplus columns, two artificials
+            assert_eq!(
+                simplex.col_kind,
+                vec![
+                    ColKind::Objective,
+                    ColKind::Variable,
+                    ColKind::Variable,
+                    ColKind::Slack,
+                    ColKind::Slack,
+                    ColKind::Slack,
+                    ColKind::Artificial,
+                    ColKind::Artificial,
+                ]
+            );
+            let t = &simplex.tableau;
+            // <= row: slack
+            assert_eq!(t[[1, 3]], 1.0);
+            // >= row: surplus and artificial
+            assert_eq!(t[[2, 4]], -1.0);
+            assert_eq!(t[[2, 6]], 1.0);
+            // = row: artificial only
+            assert_eq!(t[[3, 7]], 1.0);
+            // >= row with negative rhs is turned into a <= row
+            assert_eq!(t[[4, 1]], -1.0);
+            assert_eq!(t[[4, 2]], 1.0);
+            assert_eq!(t[[4, 5]], 1.0);
+            assert_eq!(t[[4, 8]], 3.0);
+            assert_eq!(simplex.basis, vec![3, 6, 7, 5]);
+        }
+    }
+
+    #[test]
+    fn test_strict_relation_rejected() {
+        for input in [
+            "
+max {x1 }
+st {
+    x1 < 4
+}
+",
+            "
+max {x1 }
+st {
+    x1 <= 4
+    x1 > 1
+}
+",
+        ] {
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let err = tableau(ast).err().unwrap();
+            assert!(err.to_string().contains("strict inequality"));
+        }
+    }
+
+    #[test]
+    fn test_named_constraints() {
+        let input = "
+max { profit: 3x1 + 5x2 }
+st {
+    plant_a: x1 <= 4
+    2x2 <= 12
+    plant_c: 3x1 + 2x2 <= 18
+}
+";
+        let symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        assert_eq!(symbols.obj_name.as_deref(), Some("profit"));
+        assert_eq!(symbols.row_names[&0], "plant_a");
+        assert_eq!(symbols.row_names[&2], "plant_c");
+        let sol = Simplex::build(LinearProgram::try_from(&symbols).unwrap())
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_close(sol.objective, 36.0);
+        assert_eq!(sol.row_names, vec!["plant_a", "1", "plant_c"]);
+        let text = sol.to_string();
+        assert!(text.contains("  plant_c: slack 0, dual 1"), "{}", text);
+
+        let infeasible = "
+max { x1 }
+st {
+    low: x1 <= 2
+    x1 <= 10
+    high: x1 >= 3
+}
+";
+        let sol = tableau(construct_ast(infeasible.as_bytes()).unwrap())
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert!(sol.to_string().contains("iis: low, high"));
+
+        let strict = "
+max { x1 }
+st {
+    x1 <= 4
+    cap: x1 < 4
+}
+";
+        let err = tableau(construct_ast(strict.as_bytes()).unwrap())
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("constraint cap: strict"));
+
+        let duplicate = "
+max { x1 }
+st {
+    cap: x1 <= 4
+    cap: x1 <= 5
+}
+";
+        let err = tableau(construct_ast(duplicate.as_bytes()).unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "constraint name 'cap' is used more than once"
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let input = "
+max { 3x1 + 2x2 }
+st {
+    x1 + x2 <= 50
+}
+bounds { x1 <= 40; 5 <= x2 <= 30 }
+";
+        let sol = tableau(construct_ast(input.as_bytes()).unwrap())
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert_close(sol.objective, 140.0);
+        assert_close(sol.values["x1"], 40.0);
+        assert_close(sol.values["x2"], 10.0);
+
+        let free = input.replace("x1 <= 40", "x1 free");
+        let sol = tableau(construct_ast(free.as_bytes()).unwrap())
+            .unwrap()
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_close(sol.objective, 145.0);
+        assert_close(sol.values["x1"], 45.0);
+    }
+
+    #[test]
+    fn test_variable_bounds() {
+        let input = "
+max { 3x1 + 2x2 }
+st {
+    x1 + x2 <= 50
+}
+bounds { x1 <= 40; x2 <= 30 }
+";
+        let simplex = tableau(construct_ast(input.as_bytes()).unwrap()).unwrap();
+        // the bounds are not rows of the tableau
+        assert_eq!(simplex.tableau.nrows(), 2);
+        let program = simplex.program.clone();
+        let sol = Simplex::build(program.clone())
+            .solve_with(&Default::default())
+            .unwrap();
+        assert_close(sol.objective, 140.0);
+        assert_close(sol.duals[0], 2.0);
+        // x1 sits at its upper bound and would still pay 3 - 2 per unit
+        assert_close(sol.reduced_costs["x1"], 1.0);
+        assert_close(sol.values["x2"], 10.0);
+        // x1 reaches its bound before the row is tight, without a pivot
+        let trace = Simplex::build(program.clone())
+            .solve_with(&SolverOptions {
+                trace: true,
+                ..Default::default()
+            })
+            .unwrap()
+            .trace
+            .unwrap();
+        let flip = &trace.steps[1];
+        assert_eq!(flip.entering.as_deref(), Some("x1"));
+        assert!(flip.leaving.is_none() && flip.pivot.is_none());
+
+        // the same programs on `x+ - x-` with the bounds written as rows
+        let with_rows = |program: &LinearProgram| {
+            let n_vars = program.names.len();
+            let split = |v: &[f64]| v.iter().chain(v).copied().collect::<Vec<_>>();
+            let mut rows = program.clone();
+            rows.names = (0..2 * n_vars).map(|i| format!("y{}", i)).collect();
+            rows.obj = split(&program.obj);
+            rows.obj[n_vars..].iter_mut().for_each(|c| *c = -*c);
+            let mut dense = (0..program.n_constr())
+                .map(|row| {
+                    let mut row = split(&dense_row(&program.matrix, row));
+                    row[n_vars..].iter_mut().for_each(|a| *a = -*a);
+                    row
+                })
+                .collect::<Vec<_>>();
+            for col in 0..n_vars {
+                for (bound, cmp) in [
+                    (program.lower[col], CmpOperation::Geq),
+                    (program.upper[col], CmpOperation::Leq),
+                ] {
+                    if bound.is_finite() {
+                        let mut row = vec![0.0; 2 * n_vars];
+                        row[col] = 1.0;
+                        row[n_vars + col] = -1.0;
+                        dense.push(row);
+                        rows.rhs.push(bound);
+                        rows.cmp.push(cmp);
+                        rows.row_names.push(None);
+                    }
+                }
+            }
+            rows.matrix = from_dense(2 * n_vars, &dense);
+            rows.lower = vec![0.0; 2 * n_vars];
+            rows.upper = vec![f64::INFINITY; 2 * n_vars];
+            rows
+        };
+        let mut seed = 7u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let mut infeasible = 0;
+        for _ in 0..60 {
+            let (n_vars, n_constr) = (5, 4);
+            let mut program = program.clone();
+            program.names = (0..n_vars).map(|i| format!("x{}", i)).collect();
+            program.obj = (0..n_vars).map(|_| next(9) as f64 - 3.0).collect();
+            program.matrix = from_dense(
+                n_vars,
+                &(0..n_constr)
+                    .map(|_| (0..n_vars).map(|_| next(7) as f64 - 2.0).collect())
+                    .collect::<Vec<_>>(),
+            );
+            program.rhs = (0..n_constr).map(|_| next(20) as f64 - 4.0).collect();
+            program.cmp = (0..n_constr)
+                .map(|_| match next(4) {
+                    0 => CmpOperation::Geq,
+                    1 => CmpOperation::Eq,
+                    _ => CmpOperation::Leq,
+                })
+                .collect();
+            program.row_names = vec![None; n_constr];
+            program.lower = (0..n_vars)
+                .map(|_| match next(5) {
+                    0 => -(next(4) as f64),
+                    1 => f64::NEG_INFINITY,
+                    2 => next(3) as f64,
+                    _ => 0.0,
+                })
+                .collect();
+            program.upper = program
+                .lower
+                .iter()
+                .map(|lower| match next(3) {
+                    0 => f64::INFINITY,
+                    _ => lower.max(-3.0) + next(6) as f64,
+                })
+                .collect();
+            let truth = Simplex::build(with_rows(&program))
+                .solve_with(&Default::default())
+                .unwrap();
+            for init in [InitStrategy::TwoPhase, InitStrategy::BigM(1e4)] {
+                for rule in [
+                    PivotRule::Dantzig,
+                    PivotRule::Bland,
+                    PivotRule::SteepestEdge,
+                    PivotRule::Lexicographic,
+                ] {
+                    let sol = Simplex::build(program.clone())
+                        .solve_with(&SolverOptions {
+                            init,
+                            pivot_rule: rule,
+                            ..Default::default()
+                        })
+                        .unwrap();
+                    assert_eq!(sol.status, truth.status, "{:?} {:?}", rule, init);
+                    if sol.status != Status::Optimal {
+                        continue;
+                    }
+                    assert_close(sol.objective, truth.objective);
+                    for (col, name) in program.names.iter().enumerate() {
+                        let x = sol.values[name];
+                        assert!(x >= program.lower[col] - 1e-9 && x <= program.upper[col] + 1e-9);
+                    }
+                }
+            }
+            if truth.status == Status::Infeasible {
+                infeasible += 1;
+                // with bounds the certificate reads y^T b < min over the box of y^T A x
+                let y = Simplex::build(program.clone())
+                    .solve_with(&Default::default())
+                    .unwrap()
+                    .farkas
+                    .unwrap();
+                let y_b: f64 = (0..n_constr).map(|r| y[r] * program.rhs[r]).sum();
+                let box_min: f64 = (0..n_vars)
+                    .map(|col| {
+                        let y_a: f64 = program.matrix.col_dot(col, &y);
+                        if y_a < -1e-9 {
+                            y_a * program.upper[col]
+                        } else if y_a > 1e-9 {
+                            y_a * program.lower[col]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .sum();
+                assert!(
+                    y_b < box_min - 1e-9 || box_min.is_nan(),
+                    "{} >= {}",
+                    y_b,
+                    box_min
+                );
+            }
+        }
+        assert!(infeasible > 0);
+    }
+
+    #[test]
+    fn test_min() {
+        {
+            let input = "
+min {2x1 + 3x2 }
+st {
+    x1 + x2 >= 4
+    x1 + 3x2 >= 6
+    x1 - x2 = 0
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex.solve_with(&Default::default()).unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_close(sol.objective, 10.0);
+            assert_close(sol.values["x1"], 2.0);
+            assert_close(sol.values["x2"], 2.0);
+        }
+        {
+            let input = "
+min {x1 - x2 }
+st {
+    x1 + x2 <= 5
+    x1 >= 1
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let mut simplex = tableau(ast).unwrap();
+            let sol = simplex
+                .solve_with(&SolverOptions {
+                    init: InitStrategy::BigM(100.0),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_close(sol.objective, -3.0);
+            assert_close(sol.values["x1"], 1.0);
+            assert_close(sol.values["x2"], 4.0);
+        }
+    }
+
+    #[test]
+    fn test_solution_duals() {
+        {
+            let input = "
+max {3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, Status::Optimal);
+            assert_eq!(sol.values.len(), 2);
+            for (a, b) in sol.slacks.iter().zip([2.0, 0.0, 0.0]) {
+                assert_close(*a, b);
+            }
+            for (a, b) in sol.duals.iter().zip([0.0, 1.5, 1.0]) {
+                assert_close(*a, b);
+            }
+            assert_close(sol.reduced_costs["x1"], 0.0);
+            assert_close(sol.reduced_costs["x2"], 0.0);
+        }
+        {
+            // duals of a min program with flipped and equality rows
+            let input = "
+min {2x1 + 3x2 + 4x3 }
+st {
+    x1 + x2 >= 4
+    -x1 - 3x2 <= -6
+    x1 - x2 = 0
+}
+";
+            for init in [InitStrategy::TwoPhase, InitStrategy::BigM(1000.0)] {
+                let sol = tableau(construct_ast(input.as_bytes()).unwrap())
+                    .unwrap()
+                    .solve_with(&SolverOptions {
+                        init,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                assert_eq!(sol.status, Status::Optimal);
+                assert!(sol.iterations > 0);
+                assert_close(sol.objective, 10.0);
+                for (a, b) in sol.slacks.iter().zip([0.0, 2.0, 0.0]) {
+                    assert_close(*a, b);
+                }
+                for (a, b) in sol.duals.iter().zip([2.5, 0.0, -0.5]) {
+                    assert_close(*a, b);
+                }
+                assert_close(sol.reduced_costs["x1"], 0.0);
+                assert_close(sol.reduced_costs["x3"], 4.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        {
+            let input = "
+max {3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    max_iter: Some(1),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::IterationLimit);
+            assert_eq!(sol.iterations, 1);
+        }
+    }
+
+    #[test]
+    fn test_infeasibility_certificate() {
+        {
+            let input = "
+max {x1 + x2 }
+st {
+    x1 + x2 <= 2
+    x1 >= 0.5
+    x1 + x2 >= 3
+    x2 <= 10
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let simplex = tableau(ast).unwrap();
+            let program = simplex.program.clone();
+            for init in [InitStrategy::TwoPhase, InitStrategy::BigM(1000.0)] {
+                let sol = Simplex::build(program.clone())
+                    .solve_with(&SolverOptions {
+                        init,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                assert_eq!(sol.status, Status::Infeasible);
+                assert_eq!(sol.iis, Some(vec![0, 2]));
+
+                let y = sol.farkas.unwrap();
+                for (row, cmp) in program.cmp.iter().enumerate() {
+                    match cmp {
+                        CmpOperation::Leq => assert!(y[row] >= -1e-9),
+                        CmpOperation::Geq => assert!(y[row] <= 1e-9),
+                        _ => {}
+                    }
+                }
+                for col in 0..program.names.len() {
+                    let y_a: f64 = program.matrix.col_dot(col, &y);
+                    assert!(y_a >= -1e-9);
+                }
+                let y_b: f64 = (0..y.len()).map(|r| y[r] * program.rhs[r]).sum();
+                assert!(y_b < 0.0);
+            }
+        }
+        {
+            let input = "
+max {x1 - x2 }
+st {
+    -1.21x1 >= 1000
+    x2 >= 1000
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, Status::Infeasible);
+            assert_eq!(sol.iis, Some(vec![0]));
+            let y = sol.farkas.unwrap();
+            assert!(y[0] < 0.0);
+            assert_close(y[1], 0.0);
+        }
+        {
+            let input = "
+max {x1 + x2 + x3 }
+st {
+    x1 + x2 = 4
+    x2 + x3 = 4
+    x1 + x3 >= 9
+    x1 - x3 = 0
+    x3 <= 7
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    iis: false,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::Infeasible);
+            assert!(sol.farkas.is_some());
+            assert!(sol.iis.is_none());
+
+            let sol = tableau(construct_ast(input.as_bytes()).unwrap())
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    farkas: false,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::Infeasible);
+            assert!(sol.farkas.is_none() && sol.iis.is_none());
+        }
+    }
+
+    #[test]
+    fn test_add_row() {
+        let input = "
+max { 3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+        let mut simplex = tableau(construct_ast(input.as_bytes()).unwrap()).unwrap();
+        assert_close(
+            simplex.solve_with(&Default::default()).unwrap().objective,
+            36.0,
+        );
+        // cuts off the optimum (2, 6), the dual simplex moves to (1.5, 6)
+        simplex
+            .add_row(
+                Some(String::from("cut")),
+                vec![1.0, 1.0],
+                CmpOperation::Leq,
+                7.5,
+            )
+            .unwrap();
+        let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert_close(sol.objective, 34.5);
+        assert_close(sol.values["x1"], 1.5);
+        assert_eq!(sol.row_names[3], "cut");
+        assert_close(sol.slacks[3], 0.0);
+        assert_close(sol.duals[3], 3.0);
+        simplex
+            .add_row(None, vec![1.0, 0.0], CmpOperation::Geq, 5.0)
+            .unwrap();
+        let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert!(simplex
+            .add_row(None, vec![1.0, 0.0], CmpOperation::Eq, 1.0)
+            .is_err());
+
+        // random programs with bounds and one more row each, against
+        // solving the longer program from scratch
+        let mut seed = 3u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let mut program = simplex.program.clone();
+        let mut reoptimized = 0;
+        for _ in 0..60 {
+            let (n_vars, n_constr) = (4, 3);
+            program.names = (0..n_vars).map(|i| format!("x{}", i)).collect();
+            program.obj = (0..n_vars).map(|_| next(9) as f64 - 3.0).collect();
+            program.matrix = from_dense(
+                n_vars,
+                &(0..n_constr)
+                    .map(|_| (0..n_vars).map(|_| next(7) as f64 - 2.0).collect())
+                    .collect::<Vec<_>>(),
+            );
+            program.rhs = (0..n_constr).map(|_| next(20) as f64 - 4.0).collect();
+            program.cmp = (0..n_constr)
+                .map(|_| match next(3) {
+                    0 => CmpOperation::Geq,
+                    _ => CmpOperation::Leq,
+                })
+                .collect();
+            program.row_names = vec![None; n_constr];
+            program.lower = (0..n_vars)
+                .map(|_| match next(4) {
+                    0 => -(next(4) as f64),
+                    1 => f64::NEG_INFINITY,
+                    _ => 0.0,
+                })
+                .collect();
+            program.upper = program
+                .lower
+                .iter()
+                .map(|lower| match next(2) {
+                    0 => f64::INFINITY,
+                    _ => lower.max(-3.0) + 1.0 + next(6) as f64,
+                })
+                .collect();
+            program.integer = vec![false; n_vars];
+
+            let mut simplex = Simplex::build(program.clone());
+            if simplex.solve_with(&Default::default()).unwrap().status != Status::Optimal {
+                continue;
+            }
+            let coefs = (0..n_vars)
+                .map(|_| next(7) as f64 - 3.0)
+                .collect::<Vec<_>>();
+            let cmp = if next(2) == 0 {
+                CmpOperation::Leq
+            } else {
+                CmpOperation::Geq
+            };
+            let rhs = next(12) as f64 - 6.0;
+            simplex
+                .add_row(None, coefs.clone(), cmp.clone(), rhs)
+                .unwrap();
+            let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+
+            let mut longer = program.clone();
+            longer.matrix.push_row(coefs.iter().copied().enumerate());
+            longer.cmp.push(cmp.clone());
+            longer.rhs.push(rhs);
+            longer.row_names.push(None);
+            let truth = Simplex::build(longer)
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, truth.status);
+            if truth.status == Status::Optimal {
+                reoptimized += 1;
+                assert_close(sol.objective, truth.objective);
+                let lhs: f64 = (0..n_vars)
+                    .map(|i| coefs[i] * sol.values[&program.names[i]])
+                    .sum();
+                match cmp {
+                    CmpOperation::Leq => assert!(lhs <= rhs + 1e-6),
+                    _ => assert!(lhs >= rhs - 1e-6),
+                }
+                // duals are not unique on degenerate optima, but only tight rows have one
+                for (y, slack) in sol.duals.iter().zip(&sol.slacks) {
+                    assert!(y.abs() < 1e-6 || slack.abs() < 1e-6);
+                }
+            }
+        }
+        assert!(reoptimized > 10);
+    }
+
+    #[test]
+    fn test_dual_simplex() {
+        // costs are non-negative in a min problem with >= rows: the slack
+        // basis is dual feasible, so no Phase I is needed
+        let input = "
+min { 2x + 3y }
+st {
+    x + y >= 4
+    x + 3y >= 6
+}
+";
+        let options = SolverOptions {
+            algorithm: Algorithm::Dual,
+            trace: true,
+            ..Default::default()
+        };
+        let mut simplex = tableau(construct_ast(input.as_bytes()).unwrap()).unwrap();
+        let sol = simplex.solve_with(&options).unwrap();
+        assert_eq!(sol.status, Status::Optimal);
+        assert_close(sol.objective, 9.0);
+        assert_close(sol.values["x"], 3.0);
+        assert_close(sol.values["y"], 1.0);
+        assert_close(sol.duals[0], 1.5);
+        assert_close(sol.duals[1], 0.5);
+        let trace = sol.trace.unwrap();
+        assert!(trace.steps.iter().all(|step| step.phase == Phase::Two));
+        let pivots = trace.steps.iter().filter(|step| step.entering.is_some());
+        assert_eq!(pivots.count(), sol.iterations);
+
+        // an `=` row keeps its artificial column, fixed at zero
+        let input_eq = input.replace("x + 3y >= 6", "x + 3y >= 6\n    x - y = 3");
+        let mut simplex = tableau(construct_ast(input_eq.as_bytes()).unwrap()).unwrap();
+        let sol = simplex.solve_with(&options).unwrap();
+        assert_close(sol.objective, 9.75);
+        assert_close(sol.values["x"], 3.75);
+        assert_close(sol.duals[1], 1.25);
+        assert_close(sol.duals[2], 0.75);
+
+        // the dual ratio test finds the infeasible row
+        let input_inf = input.replace("x + 3y >= 6", "x + y <= 1");
+        let mut simplex = tableau(construct_ast(input_inf.as_bytes()).unwrap()).unwrap();
+        let sol = simplex.solve_with(&options).unwrap();
+        assert_eq!(sol.status, Status::Infeasible);
+        assert!(sol.farkas.is_some());
+
+        // columns with an upper bound start there
+        let input_ub = "
+max { x + y }
+st {
+    x + y >= 4
+}
+bounds { x <= 5; y <= 2 }
+";
+        let mut simplex = tableau(construct_ast(input_ub.as_bytes()).unwrap()).unwrap();
+        let sol = simplex.solve_with(&options).unwrap();
+        assert_close(sol.objective, 7.0);
+        assert!(sol
+            .trace
+            .unwrap()
+            .steps
+            .iter()
+            .all(|step| step.phase == Phase::Two));
+
+        // no dual feasible start, the primal simplex takes over
+        let input_max = input.replace("min { 2x + 3y }", "max { 2x + 3y }");
+        let mut simplex = tableau(construct_ast(input_max.as_bytes()).unwrap()).unwrap();
+        let sol = simplex.solve_with(&options).unwrap();
+        assert_eq!(sol.status, Status::Unbounded);
+
+        // random programs with bounds and all relations, against the primal simplex
+        let mut seed = 5u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        for _ in 0..60 {
+            let (n_vars, n_constr) = (4, 3);
+            let lower = (0..n_vars)
+                .map(|_| match next(4) {
+                    0 => -(next(4) as f64),
+                    1 => f64::NEG_INFINITY,
+                    _ => 0.0,
+                })
+                .collect::<Vec<_>>();
+            let program = LinearProgram {
+                names: (0..n_vars).map(|i| format!("x{}", i)).collect(),
+                obj: (0..n_vars).map(|_| next(7) as f64 - 4.0).collect(),
+                matrix: from_dense(
+                    n_vars,
+                    &(0..n_constr)
+                        .map(|_| (0..n_vars).map(|_| next(7) as f64 - 2.0).collect())
+                        .collect::<Vec<_>>(),
+                ),
+                rhs: (0..n_constr).map(|_| next(20) as f64 - 4.0).collect(),
+                cmp: (0..n_constr)
+                    .map(|_| match next(5) {
+                        0 => CmpOperation::Eq,
+                        1 | 2 => CmpOperation::Geq,
+                        _ => CmpOperation::Leq,
+                    })
+                    .collect(),
+                sense: if next(2) == 0 { Sense::Max } else { Sense::Min },
+                row_names: vec![None; n_constr],
+                obj_name: None,
+                obj_constant: 0.0,
+                upper: lower
+                    .iter()
+                    .map(|lower| match next(2) {
+                        0 => f64::INFINITY,
+                        _ => lower.max(-3.0) + 1.0 + next(6) as f64,
+                    })
+                    .collect(),
+                lower,
+                integer: vec![false; n_vars],
+            };
+            let primal = Simplex::build(program.clone())
+                .solve_with(&Default::default())
+                .unwrap();
+            let dual = Simplex::build(program.clone())
+                .solve_with(&SolverOptions {
+                    algorithm: Algorithm::Dual,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(dual.status, primal.status);
+            if primal.status == Status::Optimal {
+                assert_close(dual.objective, primal.objective);
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_bounds() {
+        let input = "
+max { 3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+        let mut simplex = tableau(construct_ast(input.as_bytes()).unwrap()).unwrap();
+        assert_close(
+            simplex.solve_with(&Default::default()).unwrap().objective,
+            36.0,
+        );
+        // (2, 6) is cut off by x2 <= 5, the optimum moves to (8/3, 5)
+        simplex.set_bounds(1, 0.0, 5.0).unwrap();
+        let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+        assert_close(sol.objective, 33.0);
+        assert_close(sol.values["x2"], 5.0);
+        // and x1 >= 3 from there to (3, 4.5)
+        simplex.set_bounds(0, 3.0, f64::INFINITY).unwrap();
+        let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+        assert_close(sol.objective, 31.5);
+        assert_close(sol.values["x1"], 3.0);
+        assert!(simplex.set_bounds(0, 5.0, 4.0).is_err());
+        assert!(simplex.set_bounds(0, f64::NEG_INFINITY, 4.0).is_err());
+
+        // a free variable keeps its two columns and gets no rows
+        let input = "
+max { x1 + 2x2 }
+st {
+    x1 + x2 <= 4
+    x1 <= 3
+}
+bounds { x2 free }
+";
+        let mut simplex = tableau(construct_ast(input.as_bytes()).unwrap()).unwrap();
+        assert_close(
+            simplex.solve_with(&Default::default()).unwrap().objective,
+            8.0,
+        );
+        for (lower, upper, objective, x2) in [(-5.0, -2.0, -1.0, -2.0), (2.0, 3.0, 7.0, 3.0)] {
+            simplex.set_bounds(1, lower, upper).unwrap();
+            let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+            assert_close(sol.objective, objective);
+            assert_close(sol.values["x2"], x2);
+            assert_eq!(simplex.tableau.nrows(), 3);
+        }
+
+        // random programs with one variable bounded tighter, against solving
+        // them from scratch
+        let mut seed = 7u64;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let mut program = simplex.program.clone();
+        let mut reoptimized = 0;
+        for _ in 0..60 {
+            let (n_vars, n_constr) = (4, 3);
+            program.names = (0..n_vars).map(|i| format!("x{}", i)).collect();
+            program.obj = (0..n_vars).map(|_| next(9) as f64 - 3.0).collect();
+            program.matrix = from_dense(
+                n_vars,
+                &(0..n_constr)
+                    .map(|_| (0..n_vars).map(|_| next(7) as f64 - 2.0).collect())
+                    .collect::<Vec<_>>(),
+            );
+            program.rhs = (0..n_constr).map(|_| next(20) as f64 - 4.0).collect();
+            program.cmp = (0..n_constr)
+                .map(|_| match next(3) {
+                    0 => CmpOperation::Geq,
+                    _ => CmpOperation::Leq,
+                })
+                .collect();
+            program.row_names = vec![None; n_constr];
+            program.lower = (0..n_vars)
+                .map(|_| match next(3) {
+                    0 => -(next(4) as f64),
+                    1 => f64::NEG_INFINITY,
+                    _ => 0.0,
+                })
+                .collect();
+            program.upper = program
+                .lower
+                .iter()
+                .map(|lower| match next(2) {
+                    0 => f64::INFINITY,
+                    _ => lower.max(-3.0) + 1.0 + next(6) as f64,
+                })
+                .collect();
+            program.integer = vec![false; n_vars];
+
+            let mut simplex = Simplex::build(program.clone());
+            if simplex.solve_with(&Default::default()).unwrap().status != Status::Optimal {
+                continue;
+            }
+            let var = next(n_vars as u64) as usize;
+            let mut tighter = program.clone();
+            let (lower, upper) = (program.lower[var], program.upper[var]);
+            let bound = next(9) as f64 - 4.0;
+            match next(3) {
+                0 if bound > lower => tighter.lower[var] = bound.min(upper),
+                1 if bound < upper => tighter.upper[var] = bound.max(lower),
+                _ => {
+                    tighter.lower[var] = lower.max(bound - 1.0).min(upper);
+                    tighter.upper[var] = upper.min(bound + 1.0).max(tighter.lower[var]);
+                }
+            }
+            simplex
+                .set_bounds(var, tighter.lower[var], tighter.upper[var])
+                .unwrap();
+            let sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+            let truth = Simplex::build(tighter.clone())
+                .solve_with(&Default::default())
+                .unwrap();
+            assert_eq!(sol.status, truth.status);
+            if truth.status == Status::Optimal {
+                reoptimized += 1;
+                assert_close(sol.objective, truth.objective);
+                let x = sol.values[&program.names[var]];
+                assert!(x >= tighter.lower[var] - 1e-6 && x <= tighter.upper[var] + 1e-6);
+            }
+        }
+        assert!(reoptimized > 10);
+    }
+
+    #[test]
+    fn test_gomory_cuts() {
+        // the optimum of the relaxation is (1, 1.5), of the integer program (1, 1)
+        let input = "
+max { x2 }
+st {
+    3x1 + 2x2 <= 6
+    -3x1 + 2x2 <= 0
+}
+";
+        let mut symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        symbols.set_integer("x1");
+        symbols.set_integer("x2");
+        let mut simplex = Simplex::build(LinearProgram::try_from(&symbols).unwrap());
+        let mut sol = simplex.solve_with(&Default::default()).unwrap();
+        assert_close(sol.objective, 1.5);
+        for _ in 0..5 {
+            let cuts = simplex.gomory_cuts();
+            if cuts.is_empty() {
+                break;
+            }
+            for (coefs, rhs) in cuts {
+                // violated by the relaxation, kept by every integer point
+                let at = |x1: f64, x2: f64| coefs[0] * x1 + coefs[1] * x2;
+                assert!(at(sol.values["x1"], sol.values["x2"]) < rhs - 1e-9);
+                for (x1, x2) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (2.0, 0.0)] {
+                    assert!(at(x1, x2) >= rhs - 1e-9, "{:?} >= {}", coefs, rhs);
+                }
+                simplex
+                    .add_row(None, coefs, CmpOperation::Geq, rhs)
+                    .unwrap();
+            }
+            sol = simplex.reoptimize(&SolverOptions::default()).unwrap();
+        }
+        assert_close(sol.objective, 1.0);
+        assert_eq!(sol.slacks.len(), simplex.program.n_constr());
+
+        // a continuous variable gets no integrality from its bounds
+        let mut symbols = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        symbols.set_integer("x2");
+        let mut simplex = Simplex::build(LinearProgram::try_from(&symbols).unwrap());
+        simplex.solve_with(&Default::default()).unwrap();
+        for (coefs, rhs) in simplex.gomory_cuts() {
+            // x1 = 2/3 with x2 = 1 stays feasible
+            assert!(coefs[0] * 2.0 / 3.0 + coefs[1] >= rhs - 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_pivot_rules() {
+        // Beale's example cycles with Dantzig's rule and first-row ties
+        let input = "
+max {0.75x4 - 150x5 + 0.02x6 - 6x7 }
+st {
+    0.25x4 - 60x5 - 0.04x6 + 9x7 <= 0
+    0.5x4 - 90x5 - 0.02x6 + 3x7 <= 0
+    x6 <= 1
+}
+";
+        {
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    bland_after: None,
+                    max_iter: Some(100),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::IterationLimit);
+        }
+        for (pivot_rule, bland_after) in [
+            (PivotRule::Dantzig, Some(5)),
+            (PivotRule::Bland, None),
+            (PivotRule::Lexicographic, None),
+            (PivotRule::SteepestEdge, Some(5)),
+        ] {
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    pivot_rule,
+                    bland_after,
+                    max_iter: Some(100),
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(sol.status, Status::Optimal, "{:?}", pivot_rule);
+            assert_close(sol.objective, 0.05);
+            assert_close(sol.values["x6"], 1.0);
+        }
+        {
+            let input = "
+min {2x1 + 3x2 }
+st {
+    x1 + x2 >= 4
+    x1 + 3x2 >= 6
+    x1 - x2 = 0
+}
+";
+            for pivot_rule in [
+                PivotRule::Dantzig,
+                PivotRule::Bland,
+                PivotRule::Lexicographic,
+                PivotRule::SteepestEdge,
+            ] {
+                let ast = construct_ast(input.as_bytes()).unwrap();
+                let sol = tableau(ast)
+                    .unwrap()
+                    .solve_with(&SolverOptions {
+                        pivot_rule,
+                        ..Default::default()
+                    })
+                    .unwrap();
+                assert_eq!(sol.status, Status::Optimal);
+                assert_close(sol.objective, 10.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        {
+            let input = "
+max {3x1 + 5x2 }
+st {
+    x1 <= 4
+    2x2 <= 12
+    3x1 + 2x2 <= 18
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    trace: true,
+                    ..Default::default()
+                })
+                .unwrap();
+            let trace = sol.trace.unwrap();
+            assert_eq!(
+                trace.columns,
+                vec!["z", "x1", "x2", "s_0", "s_1", "s_2", "rhs"]
+            );
+            assert_eq!(trace.steps.len(), sol.iterations + 1);
+            let first = &trace.steps[1];
+            assert_eq!(first.entering.as_deref(), Some("x2"));
+            assert_eq!(first.leaving.as_deref(), Some("s_1"));
+            assert_eq!(first.pivot, Some(2.0));
+            assert_close(first.objective, 30.0);
+            let last = trace.steps.last().unwrap();
+            assert_close(last.objective, 36.0);
+            assert_eq!(last.row_labels, vec!["z", "s_0", "x2", "x1"]);
+        }
+        {
+            let input = "
+min {2x1 + 3x2 }
+st {
+    x1 + x2 >= 4
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    trace: true,
+                    ..Default::default()
+                })
+                .unwrap();
+            let trace = sol.trace.unwrap();
+            assert_eq!(trace.steps[0].phase, Phase::One);
+            assert_eq!(trace.steps[0].row_labels, vec!["z", "a_0", "w"]);
+            assert_close(trace.steps[0].objective, -4.0);
+            assert_eq!(trace.steps.last().unwrap().phase, Phase::Two);
+            assert_close(trace.steps.last().unwrap().objective, 8.0);
+
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let sol = tableau(ast)
+                .unwrap()
+                .solve_with(&SolverOptions {
+                    init: InitStrategy::BigM(100.0),
+                    trace: true,
+                    ..Default::default()
+                })
+                .unwrap();
+            let trace = sol.trace.unwrap();
+            assert!(trace.steps.iter().all(|step| step.phase == Phase::BigM));
+            assert_eq!(trace.steps[0].row_labels, vec!["z", "a_0"]);
+            assert_close(trace.steps.last().unwrap().objective, 8.0);
         }
     }
 }
@@ -0,0 +1,423 @@
+use std::{collections::HashMap, fmt, fmt::Write};
+
+use crate::semantics::trace::Trace;
+
+/// Outcome of a solver run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Optimal,
+    Infeasible,
+    Unbounded,
+    IterationLimit,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Status::Optimal => "optimal",
+            Status::Infeasible => "infeasible",
+            Status::Unbounded => "unbounded",
+            Status::IterationLimit => "iteration limit",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Serialization of a solution, see `Solution::render`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => anyhow::bail!("unknown format '{}', expected json, csv or text", s),
+        }
+    }
+}
+
+/// Result of a solver run, all values are reported in the sense (`min` or
+/// `max`) of the original program.
+///
+/// Rows are identified by their index in the `st { }` block, `row_names`
+/// holds the name they are reported under.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub status: Status,
+    /// Objective value, `+-inf` if unbounded and `NaN` if infeasible
+    pub objective: f64,
+    /// Primal value of every variable
+    pub values: HashMap<String, f64>,
+    /// Distance of every row's activity to its right hand side, 0 for `=` rows
+    pub slacks: Vec<f64>,
+    /// Dual price of every row: change of the objective per unit increase of the right hand side
+    pub duals: Vec<f64>,
+    /// Reduced cost of every variable: change of the objective per unit increase of the variable
+    pub reduced_costs: HashMap<String, f64>,
+    /// Number of pivots over all phases
+    pub iterations: usize,
+    /// Certificate of unboundedness: direction `d` with `x + t*d` feasible for all
+    /// `t >= 0` that improves the objective, by variable name
+    pub ray: Option<HashMap<String, f64>>,
+    /// Certificate of infeasibility: multipliers `y` of the rows with `y_i >= 0` on
+    /// `<=` rows and `y_i <= 0` on `>=` rows such that `y^T A >= 0` and `y^T b < 0`,
+    /// so the weighted sum of the rows reads `0 <= y^T A x <= y^T b < 0`. With other
+    /// bounds than `x >= 0` the sum reads `min over l <= x <= u of y^T A x <= y^T b`
+    /// with `y^T b` below the left end
+    pub farkas: Option<Vec<f64>>,
+    /// Irreducible infeasible subset: rows that are infeasible together but
+    /// feasible as soon as any one of them is dropped
+    pub iis: Option<Vec<usize>>,
+    /// Every tableau of the run if requested with `SolverOptions::trace`
+    pub trace: Option<Trace>,
+    /// Name of every row, its index if it has none
+    pub row_names: Vec<String>,
+    /// Number of branch-and-bound nodes solved, None for a program without
+    /// integer variables
+    pub nodes: Option<usize>,
+    /// Best bound on the objective left by branch-and-bound, the optimum
+    /// lies between `objective` and it
+    pub bound: Option<f64>,
+}
+
+impl Solution {
+    /// Solution without any values, used for infeasible programs. Its
+    /// objective is NaN, which the output leaves out.
+    pub fn empty(status: Status, iterations: usize) -> Self {
+        Self {
+            status,
+            objective: f64::NAN,
+            values: HashMap::new(),
+            slacks: vec![],
+            duals: vec![],
+            reduced_costs: HashMap::new(),
+            iterations,
+            ray: None,
+            farkas: None,
+            iis: None,
+            trace: None,
+            row_names: vec![],
+            nodes: None,
+            bound: None,
+        }
+    }
+
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.to_string(),
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Csv => self.to_csv(),
+        }
+    }
+
+    /// Name of row `row`, its index if there are no names
+    pub fn row_label(&self, row: usize) -> String {
+        self.row_names
+            .get(row)
+            .cloned()
+            .unwrap_or_else(|| row.to_string())
+    }
+
+    /// One JSON object, non-finite numbers (unbounded or infeasible
+    /// objective) are written as `null`
+    pub fn to_json(&self) -> String {
+        let map = |m: &HashMap<String, f64>| {
+            let entries = sorted(m)
+                .into_iter()
+                .map(|(k, v)| format!("{}: {}", json_string(k), json_num(*v)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", entries.join(", "))
+        };
+        let list = |l: &[f64]| {
+            let entries = l.iter().map(|v| json_num(*v)).collect::<Vec<_>>();
+            format!("[{}]", entries.join(", "))
+        };
+
+        let names = self
+            .row_names
+            .iter()
+            .map(|n| json_string(n))
+            .collect::<Vec<_>>();
+        let mut fields = vec![
+            ("status", json_string(&self.status.to_string())),
+            ("objective", json_num(self.objective)),
+            ("iterations", self.iterations.to_string()),
+            ("variables", map(&self.values)),
+            ("reduced_costs", map(&self.reduced_costs)),
+            ("rows", format!("[{}]", names.join(", "))),
+            ("slacks", list(&self.slacks)),
+            ("duals", list(&self.duals)),
+        ];
+        if let Some(nodes) = self.nodes {
+            fields.push(("nodes", nodes.to_string()));
+        }
+        if let Some(bound) = self.bound {
+            fields.push(("bound", json_num(bound)));
+        }
+        if let Some(ray) = &self.ray {
+            fields.push(("ray", map(ray)));
+        }
+        if let Some(farkas) = &self.farkas {
+            fields.push(("farkas", list(farkas)));
+        }
+        if let Some(iis) = &self.iis {
+            let rows = iis.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+            fields.push(("iis", format!("[{}]", rows.join(", "))));
+        }
+
+        let mut out = String::from("{\n");
+        for (i, (key, value)) in fields.iter().enumerate() {
+            let sep = if i + 1 < fields.len() { "," } else { "" };
+            let _ = writeln!(out, "  \"{}\": {}{}", key, value, sep);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Long table with header `kind,name,value,marginal`: one line for the
+    /// status and objective, every variable (marginal is the reduced cost)
+    /// and every row (value is the slack, marginal the dual). The objective
+    /// value is empty without a solution.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("kind,name,value,marginal\n");
+        let _ = writeln!(out, "status,,{},", self.status);
+        if self.objective.is_nan() {
+            out.push_str("objective,,,\n");
+        } else {
+            let _ = writeln!(out, "objective,,{},", self.objective);
+        }
+        let _ = writeln!(out, "iterations,,{},", self.iterations);
+        if let Some(nodes) = self.nodes {
+            let _ = writeln!(out, "nodes,,{},", nodes);
+        }
+        if let Some(bound) = self.bound {
+            let _ = writeln!(out, "bound,,{},", bound);
+        }
+        for (name, value) in sorted(&self.values) {
+            let reduced = self.reduced_costs.get(name).copied().unwrap_or(0.0);
+            let _ = writeln!(out, "variable,{},{},{}", csv_field(name), value, reduced);
+        }
+        for (row, (slack, dual)) in self.slacks.iter().zip(&self.duals).enumerate() {
+            let _ = writeln!(
+                out,
+                "row,{},{},{}",
+                csv_field(&self.row_label(row)),
+                slack,
+                dual
+            );
+        }
+        out
+    }
+}
+
+fn json_num(v: f64) -> String {
+    if v.is_finite() {
+        v.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Entries of a map sorted by name, so that output does not depend on hashing
+fn sorted(map: &HashMap<String, f64>) -> Vec<(&String, &f64)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Round-off below `1e-9` printed as `0` instead of `-0` or `-2.2e-16`
+fn clamp(v: f64) -> f64 {
+    if v.abs() < 1e-9 {
+        0.0
+    } else {
+        v
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "status: {}", self.status)?;
+        if !self.objective.is_nan() {
+            writeln!(f, "objective: {}", clamp(self.objective))?;
+        }
+        writeln!(f, "iterations: {}", self.iterations)?;
+        if let Some(nodes) = self.nodes {
+            writeln!(f, "nodes: {}", nodes)?;
+        }
+        if let Some(bound) = self.bound {
+            writeln!(f, "bound: {}", clamp(bound))?;
+        }
+        if !self.values.is_empty() {
+            writeln!(f, "variables:")?;
+            for (name, value) in sorted(&self.values) {
+                let reduced = self.reduced_costs.get(name).copied().unwrap_or(0.0);
+                writeln!(
+                    f,
+                    "  {} = {} (reduced cost {})",
+                    name,
+                    clamp(*value),
+                    clamp(reduced)
+                )?;
+            }
+        }
+        if !self.slacks.is_empty() {
+            writeln!(f, "rows:")?;
+            for (row, (slack, dual)) in self.slacks.iter().zip(&self.duals).enumerate() {
+                writeln!(
+                    f,
+                    "  {}: slack {}, dual {}",
+                    self.row_label(row),
+                    clamp(*slack),
+                    clamp(*dual)
+                )?;
+            }
+        }
+        if let Some(ray) = &self.ray {
+            writeln!(f, "ray:")?;
+            for (name, value) in sorted(ray) {
+                writeln!(f, "  {} = {}", name, clamp(*value))?;
+            }
+        }
+        if let Some(farkas) = &self.farkas {
+            writeln!(f, "farkas:")?;
+            for (row, y) in farkas.iter().enumerate() {
+                writeln!(f, "  {}: {}", self.row_label(row), clamp(*y))?;
+            }
+        }
+        if let Some(iis) = &self.iis {
+            let rows = iis.iter().map(|r| self.row_label(*r)).collect::<Vec<_>>();
+            writeln!(f, "iis: {}", rows.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{OutputFormat, Solution, Status};
+
+    fn solution() -> Solution {
+        let mut solution = Solution::empty(Status::Optimal, 2);
+        solution.objective = 36.0;
+        solution.values = HashMap::from([(String::from("x2"), 6.0), (String::from("x1"), 2.0)]);
+        solution.reduced_costs =
+            HashMap::from([(String::from("x2"), 0.0), (String::from("x1"), 0.0)]);
+        solution.slacks = vec![2.0, 0.0];
+        solution.duals = vec![0.0, 1.5];
+        solution.row_names = vec![String::from("plant_a"), String::from("1")];
+        solution
+    }
+
+    #[test]
+    fn test_json() {
+        let truth = r#"{
+  "status": "optimal",
+  "objective": 36,
+  "iterations": 2,
+  "variables": {"x1": 2, "x2": 6},
+  "reduced_costs": {"x1": 0, "x2": 0},
+  "rows": ["plant_a", "1"],
+  "slacks": [2, 0],
+  "duals": [0, 1.5]
+}
+"#;
+        assert_eq!(solution().render(OutputFormat::Json), truth);
+
+        let mut unbounded = Solution::empty(Status::Unbounded, 1);
+        unbounded.objective = f64::INFINITY;
+        unbounded.ray = Some(HashMap::from([(String::from("x\"1"), 1.0)]));
+        let json = unbounded.to_json();
+        assert!(json.contains("\"objective\": null,"));
+        assert!(json.contains("\"ray\": {\"x\\\"1\": 1}\n"));
+    }
+
+    #[test]
+    fn test_text() {
+        let mut solution = solution();
+        solution.values.insert(String::from("x1"), -0.0);
+        solution.reduced_costs.insert(String::from("x2"), -2.2e-16);
+        solution.duals[1] = -1.5;
+        let text = solution.render(OutputFormat::Text);
+        assert!(text.contains("status: optimal\nobjective: 36\n"));
+        assert!(text.contains("  x1 = 0 (reduced cost 0)\n  x2 = 6 (reduced cost 0)\n"));
+        assert!(text.contains("  1: slack 0, dual -1.5\n"));
+        assert!(!text.contains("-0") && !text.contains("e-16"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let truth = "kind,name,value,marginal
+status,,optimal,
+objective,,36,
+iterations,,2,
+variable,x1,2,0
+variable,x2,6,0
+row,plant_a,2,0
+row,1,0,1.5
+";
+        assert_eq!(solution().render(OutputFormat::Csv), truth);
+
+        let mut integer = solution();
+        integer.nodes = Some(5);
+        integer.bound = Some(36.5);
+        let csv = integer.to_csv();
+        assert!(csv.contains("iterations,,2,\nnodes,,5,\nbound,,36.5,\nvariable"));
+        assert!(integer
+            .to_json()
+            .contains("  \"nodes\": 5,\n  \"bound\": 36.5\n"));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let mut infeasible = Solution::empty(Status::Infeasible, 3);
+        infeasible.farkas = Some(vec![-1.0]);
+        infeasible.row_names = vec![String::from("demand")];
+        assert_eq!(
+            infeasible.render(OutputFormat::Text),
+            "status: infeasible\niterations: 3\nfarkas:\n  demand: -1\n"
+        );
+        assert!(infeasible
+            .render(OutputFormat::Csv)
+            .starts_with("kind,name,value,marginal\nstatus,,infeasible,\nobjective,,,\n"));
+        assert!(infeasible
+            .render(OutputFormat::Json)
+            .contains("  \"objective\": null,\n"));
+
+        let limit = Solution::empty(Status::IterationLimit, 100).render(OutputFormat::Text);
+        assert_eq!(limit, "status: iteration limit\niterations: 100\n");
+    }
+}
@@ -0,0 +1,221 @@
+/// Sparse `m x n` matrix in compressed sparse column (CSC) form, with a
+/// compressed sparse row copy for access by row. Every column and row lists
+/// its entries by index and holds no explicit zeros, so equal matrices
+/// compare equal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMatrix {
+    n_rows: usize,
+    n_cols: usize,
+    /// where every column starts in `row_index` and `col_values`, then the end
+    col_start: Vec<usize>,
+    row_index: Vec<usize>,
+    col_values: Vec<f64>,
+    /// where every row starts in `col_index` and `row_values`, then the end
+    row_start: Vec<usize>,
+    col_index: Vec<usize>,
+    row_values: Vec<f64>,
+}
+
+impl SparseMatrix {
+    /// Matrix of the entries `(row, col, value)` in any order, duplicates
+    /// are summed up
+    pub fn from_triplets(
+        n_rows: usize,
+        n_cols: usize,
+        entries: impl IntoIterator<Item = (usize, usize, f64)>,
+    ) -> Self {
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(i, j, _)| (*j, *i));
+        let mut merged: Vec<(usize, usize, f64)> = Vec::with_capacity(entries.len());
+        for (i, j, a) in entries {
+            assert!(
+                i < n_rows && j < n_cols,
+                "entry ({}, {}) outside of a {} x {} matrix",
+                i,
+                j,
+                n_rows,
+                n_cols
+            );
+            match merged.last_mut() {
+                Some((r, c, value)) if (*r, *c) == (i, j) => *value += a,
+                _ => merged.push((i, j, a)),
+            }
+        }
+        merged.retain(|(_, _, a)| *a != 0.0);
+
+        let mut col_start = vec![0; n_cols + 1];
+        let mut row_start = vec![0; n_rows + 1];
+        for (i, j, _) in &merged {
+            col_start[j + 1] += 1;
+            row_start[i + 1] += 1;
+        }
+        for k in 0..n_cols {
+            col_start[k + 1] += col_start[k];
+        }
+        for k in 0..n_rows {
+            row_start[k + 1] += row_start[k];
+        }
+        // the entries are sorted by column, so every row gets its columns in order
+        let mut next = row_start.clone();
+        let mut col_index = vec![0; merged.len()];
+        let mut row_values = vec![0.0; merged.len()];
+        for (i, j, a) in &merged {
+            col_index[next[*i]] = *j;
+            row_values[next[*i]] = *a;
+            next[*i] += 1;
+        }
+        Self {
+            n_rows,
+            n_cols,
+            col_start,
+            row_index: merged.iter().map(|(i, _, _)| *i).collect(),
+            col_values: merged.iter().map(|(_, _, a)| *a).collect(),
+            row_start,
+            col_index,
+            row_values,
+        }
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    /// Nonzero entries of a column as row and value
+    pub fn col(&self, col: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.col_start[col]..self.col_start[col + 1];
+        self.row_index[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.col_values[range].iter().copied())
+    }
+
+    /// Nonzero entries of a row as column and value
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.row_start[row]..self.row_start[row + 1];
+        self.col_index[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.row_values[range].iter().copied())
+    }
+
+    /// Nonzero entries as row, column and value, column by column
+    pub fn entries(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        (0..self.n_cols).flat_map(move |j| self.col(j).map(move |(i, a)| (i, j, a)))
+    }
+
+    /// `y^T a_j` for the column `col`
+    pub fn col_dot(&self, col: usize, y: &[f64]) -> f64 {
+        self.col(col).map(|(i, a)| y[i] * a).sum()
+    }
+
+    /// `y^T A`, summed up row by row over the nonzeros of `y`, which is
+    /// cheap for the sparse `y` of a pivot row
+    pub fn transpose_mul(&self, y: &[f64]) -> Vec<f64> {
+        let mut product = vec![0.0; self.n_cols];
+        for (row, y) in y.iter().enumerate() {
+            if *y != 0.0 {
+                for (j, a) in self.row(row) {
+                    product[j] += y * a;
+                }
+            }
+        }
+        product
+    }
+
+    /// Append a row given by its nonzeros as column and value
+    pub fn push_row(&mut self, coefs: impl IntoIterator<Item = (usize, f64)>) {
+        let row = self.n_rows;
+        let entries = self
+            .entries()
+            .chain(coefs.into_iter().map(|(j, a)| (row, j, a)))
+            .collect::<Vec<_>>();
+        *self = Self::from_triplets(row + 1, self.n_cols, entries);
+    }
+
+    /// Matrix of the given rows, in the given order
+    pub fn select_rows(&self, rows: &[usize]) -> Self {
+        let entries = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| self.row(*row).map(move |(j, a)| (i, j, a)));
+        Self::from_triplets(rows.len(), self.n_cols, entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        semantics::sparse::SparseMatrix,
+        test_util::{dense_row, from_dense},
+    };
+
+    #[test]
+    fn test_sparse_matrix() {
+        let dense = vec![
+            vec![1.0, 0.0, 2.0, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0],
+            vec![0.0, -3.0, 4.0, 5.0],
+        ];
+        let matrix = from_dense(4, &dense);
+        assert_eq!((matrix.n_rows(), matrix.n_cols()), (3, 4));
+        assert_eq!(matrix.entries().count(), 5);
+        assert_eq!(matrix.col(2).collect::<Vec<_>>(), vec![(0, 2.0), (2, 4.0)]);
+        assert_eq!(matrix.col(0).collect::<Vec<_>>(), vec![(0, 1.0)]);
+        assert_eq!(matrix.row(1).count(), 0);
+        assert_eq!(
+            matrix.row(2).collect::<Vec<_>>(),
+            vec![(1, -3.0), (2, 4.0), (3, 5.0)]
+        );
+        for (i, row) in dense.iter().enumerate() {
+            assert_eq!(&dense_row(&matrix, i), row);
+        }
+
+        // duplicates add up, entries that cancel are dropped
+        let triplets = SparseMatrix::from_triplets(
+            3,
+            4,
+            [
+                (2, 3, 5.0),
+                (0, 2, 1.5),
+                (2, 1, -3.0),
+                (0, 0, 1.0),
+                (0, 2, 0.5),
+                (2, 2, 4.0),
+                (1, 1, 1.0),
+                (1, 1, -1.0),
+            ],
+        );
+        assert_eq!(triplets, matrix);
+        assert_eq!(
+            matrix.entries().collect::<Vec<_>>(),
+            vec![
+                (0, 0, 1.0),
+                (2, 1, -3.0),
+                (0, 2, 2.0),
+                (2, 2, 4.0),
+                (2, 3, 5.0)
+            ]
+        );
+
+        let y = [1.0, 7.0, -1.0];
+        assert_eq!(matrix.transpose_mul(&y), vec![1.0, 3.0, -2.0, -5.0]);
+        assert_eq!(matrix.col_dot(2, &y), -2.0);
+
+        let mut longer = matrix.clone();
+        longer.push_row([(3, 1.0), (1, 0.0)]);
+        assert_eq!(longer.n_rows(), 4);
+        assert_eq!(longer.row(3).collect::<Vec<_>>(), vec![(3, 1.0)]);
+        assert_eq!(longer.col(3).collect::<Vec<_>>(), vec![(2, 5.0), (3, 1.0)]);
+
+        let subset = longer.select_rows(&[3, 0]);
+        assert_eq!(
+            subset,
+            from_dense(4, &[vec![0.0, 0.0, 0.0, 1.0], dense[0].clone()])
+        );
+        assert_eq!(SparseMatrix::from_triplets(2, 3, []).entries().count(), 0);
+    }
+}
@@ -1,60 +1,164 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use crate::{
-    lexer::tokens::{ArithOperation, Token, F64},
+    lexer::tokens::{ArithOperation, CmpOperation, Token, F64},
     parser::ast::{ASTNodeTypes, AST},
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Symbol {
-    Obj(F64),         // coefficient
-    Constr(u32, F64), // (row, coefficient)
-    RHS(u32, F64),    // row, value
+    Obj(F64),               // coefficient
+    Constr(u32, F64),       // (row, coefficient)
+    RHS(u32, F64),          // row, value
+    Cmp(u32, CmpOperation), // row, relation between expression and RHS
 }
 
-// TODO: Instead of hashmap, better vec<vec<>> per row
-/// Variables and their coeefficients
+/// Keys of the right hand sides and the relations of the rows in
+/// `SymbolTable::table`, no variable can be named like them
+pub const RESERVED_NAMES: [&str; 2] = ["RHS", "CMP"];
+
+/// Optimization sense of the objective
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Sense {
+    Max,
+    Min,
+}
+
+/// Range `lower <= x <= upper` of a variable, infinite ends are unbounded
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Bounds {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Default for Bounds {
+    /// Variables are non-negative unless declared otherwise
+    fn default() -> Self {
+        Self {
+            lower: 0.0,
+            upper: f64::INFINITY,
+        }
+    }
+}
+
+impl Bounds {
+    pub const FREE: Bounds = Bounds {
+        lower: f64::NEG_INFINITY,
+        upper: f64::INFINITY,
+    };
+
+    /// Apply `x (op) value`, `=` fixes both ends
+    pub fn restrict(&mut self, op: &CmpOperation, value: f64) {
+        match op {
+            CmpOperation::Leq | CmpOperation::Lt => self.upper = value,
+            CmpOperation::Geq | CmpOperation::Gt => self.lower = value,
+            CmpOperation::Eq => {
+                self.lower = value;
+                self.upper = value;
+            }
+        }
+    }
+}
+
+/// Variables and their coeefficients as parsed, the entries of every column
+/// of the constraint matrix. `LinearProgram` collects them into a
+/// `SparseMatrix` without a dense copy.
 #[derive(Debug)]
 pub struct SymbolTable {
     pub table: HashMap<String, Vec<Symbol>>,
     pub n_constr: u32,
+    pub sense: Sense,
+    /// `name:` of the constraints that have one, by row
+    pub row_names: HashMap<u32, String>,
+    pub obj_name: Option<String>,
+    /// constant added to the objective
+    pub obj_constant: f64,
+    /// declared bounds, variables missing here have the default `[0, inf)`
+    pub bounds: HashMap<String, Bounds>,
+    /// variables restricted to integer values
+    pub integers: HashSet<String>,
 }
 
 impl SymbolTable {
-    pub fn get_obj_symbols(&self) -> Vec<Symbol> {
-        let mut symbols = vec![];
-        for (_, v) in &self.table {
-            for symbol in v {
-                match symbol {
-                    Symbol::Obj(_) => {
-                        symbols.push(symbol.clone());
-                    }
-                    _ => {}
-                }
-            }
+    pub fn new(sense: Sense) -> Self {
+        Self {
+            table: HashMap::new(),
+            n_constr: 0,
+            sense,
+            row_names: HashMap::new(),
+            obj_name: None,
+            obj_constant: 0.0,
+            bounds: HashMap::new(),
+            integers: HashSet::new(),
         }
-        symbols
     }
-    pub fn get_row_symbols(&self, row: u32) -> Vec<Symbol> {
-        let mut symbols = vec![];
-        for (_, v) in &self.table {
-            for symbol in v {
-                match symbol {
-                    Symbol::Constr(r, _) => {
-                        if *r == row {
-                            symbols.push(symbol.clone());
-                        }
-                    }
-                    Symbol::RHS(r, _) => {
-                        if *r == row {
-                            symbols.push(symbol.clone());
-                        }
-                    }
-                    _ => {}
-                }
+
+    /// Bounds of a variable
+    pub fn bounds(&self, name: &str) -> Bounds {
+        self.bounds.get(name).copied().unwrap_or_default()
+    }
+
+    /// Bounds of a variable to change, declaring the variable if it is new
+    pub fn bounds_mut(&mut self, name: &str) -> &mut Bounds {
+        self.table.entry(name.to_string()).or_default();
+        self.bounds.entry(name.to_string()).or_default()
+    }
+
+    /// Restrict a variable to integer values, declaring it if it is new
+    pub fn set_integer(&mut self, name: &str) {
+        self.table.entry(name.to_string()).or_default();
+        self.integers.insert(name.to_string());
+    }
+
+    /// Restrict a variable to 0 or 1, replacing its bounds
+    pub fn set_binary(&mut self, name: &str) {
+        self.set_integer(name);
+        *self.bounds_mut(name) = Bounds {
+            lower: 0.0,
+            upper: 1.0,
+        };
+    }
+
+    /// Append the row `name: coefs (cmp) rhs`, returns its index
+    pub fn push_row(
+        &mut self,
+        name: Option<String>,
+        coefs: &[(String, f64)],
+        cmp: CmpOperation,
+        rhs: f64,
+    ) -> u32 {
+        let row = self.n_constr;
+        if let Some(name) = name {
+            self.row_names.insert(row, name);
+        }
+        for (name, value) in coefs {
+            self.insert(name, Symbol::Constr(row, F64(*value)));
+        }
+        self.insert("RHS", Symbol::RHS(row, F64(rhs)));
+        self.insert("CMP", Symbol::Cmp(row, cmp));
+        self.n_constr += 1;
+        row
+    }
+
+    /// Append a symbol to the entry `key`, a variable name or `RHS`/`CMP`
+    pub fn insert(&mut self, key: &str, symbol: Symbol) {
+        self.table.entry(key.to_string()).or_default().push(symbol);
+    }
+
+    /// Fails for a variable named like one of the `RESERVED_NAMES`, whose
+    /// coefficients would end up among the symbols of the rows
+    pub fn check_names(&self) -> anyhow::Result<()> {
+        for name in RESERVED_NAMES {
+            let used = self.table.get(name).is_some_and(|symbols| {
+                symbols
+                    .iter()
+                    .any(|s| matches!(s, Symbol::Obj(_) | Symbol::Constr(_, _)))
+            });
+            if used || self.bounds.contains_key(name) || self.integers.contains(name) {
+                anyhow::bail!("variable name '{}' is reserved", name);
             }
         }
-        symbols
+        Ok(())
     }
 
     fn collect_objective_symbols(&mut self, ast: &AST, root: usize, prev_sign: i8) -> i8 {
@@ -74,6 +178,12 @@ impl SymbolTable {
                         }
                     }
                 }
+                Token::Fun(fun) if fun == "min" => {
+                    self.sense = Sense::Min;
+                }
+                Token::Label(name) => {
+                    self.obj_name = Some(name.clone());
+                }
                 Token::ArithOp(op) => match op {
                     ArithOperation::Add => {
                         sign = 1;
@@ -100,11 +210,8 @@ impl SymbolTable {
         if let Some(children) = &node.children {
             for (i, child) in children.iter().enumerate() {
                 let child_node = &ast.nodes[*child];
-                match child_node.node_type {
-                    ASTNodeTypes::Constraint => {
-                        self.collect_constraint_symbol(ast, *child, i as u32, 1);
-                    }
-                    _ => {}
+                if child_node.node_type == ASTNodeTypes::Constraint {
+                    self.collect_constraint_symbol(ast, *child, i as u32, 1);
                 }
             }
         }
@@ -128,18 +235,27 @@ impl SymbolTable {
                         }
                     }
                 }
-                Token::Num(n) => {
-                    if node.node_type == ASTNodeTypes::RHS {
-                        match self.table.entry("RHS".to_string()) {
-                            Entry::Occupied(mut e) => {
-                                e.get_mut().push(Symbol::RHS(row, n.clone()));
-                            }
-                            Entry::Vacant(e) => {
-                                e.insert(vec![Symbol::RHS(row, n.clone())]);
-                            }
+                Token::Num(n) if node.node_type == ASTNodeTypes::RHS => {
+                    match self.table.entry("RHS".to_string()) {
+                        Entry::Occupied(mut e) => {
+                            e.get_mut().push(Symbol::RHS(row, n.clone()));
+                        }
+                        Entry::Vacant(e) => {
+                            e.insert(vec![Symbol::RHS(row, n.clone())]);
                         }
                     }
                 }
+                Token::Label(name) => {
+                    self.row_names.insert(row, name.clone());
+                }
+                Token::Cmp(op) => match self.table.entry("CMP".to_string()) {
+                    Entry::Occupied(mut e) => {
+                        e.get_mut().push(Symbol::Cmp(row, op.clone()));
+                    }
+                    Entry::Vacant(e) => {
+                        e.insert(vec![Symbol::Cmp(row, op.clone())]);
+                    }
+                },
                 Token::ArithOp(op) => match op {
                     ArithOperation::Add => sign = 1,
                     ArithOperation::Sub => sign = -1,
@@ -161,14 +277,65 @@ impl SymbolTable {
         }
         new_sign
     }
+
+    /// `lower <= x`, `x <= upper`, both or `x free` per statement, a later
+    /// statement replaces the ends of a variable it sets
+    fn collect_bounds(&mut self, ast: &AST, root: usize) {
+        for bound in ast.nodes[root].children.iter().flatten() {
+            let tokens = ast.nodes[*bound]
+                .children
+                .iter()
+                .flatten()
+                .filter_map(|child| ast.nodes[*child].data_index)
+                .map(|data_index| &ast.data[data_index])
+                .collect::<Vec<_>>();
+            let Some(var) = tokens
+                .iter()
+                .position(|t| matches!(t, Token::Variable(_, _)))
+            else {
+                continue;
+            };
+            let Token::Variable(name, _) = tokens[var] else {
+                continue;
+            };
+            let bounds = self.bounds_mut(name);
+            if let [Token::Num(value), Token::Cmp(op)] = tokens[..var] {
+                bounds.restrict(&op.reversed(), value.0);
+            }
+            match tokens[var + 1..] {
+                [Token::Fun(_)] => *bounds = Bounds::FREE,
+                [Token::Cmp(op), Token::Num(value)] => bounds.restrict(op, value.0),
+                _ => {}
+            }
+        }
+    }
+
+    /// Names of an `int { }` or `bin { }` section, a binary variable gets
+    /// the bounds `[0, 1]` whatever the bounds section says
+    fn collect_integers(&mut self, ast: &AST, root: usize) {
+        let tokens = ast.nodes[root]
+            .children
+            .iter()
+            .flatten()
+            .filter_map(|child| ast.nodes[*child].data_index)
+            .map(|data_index| &ast.data[data_index])
+            .collect::<Vec<_>>();
+        let binary = tokens.first() == Some(&&Token::Fun(String::from("bin")));
+        for token in tokens {
+            if let Token::Variable(name, _) = token {
+                if binary {
+                    self.set_binary(name);
+                } else {
+                    self.set_integer(name);
+                }
+            }
+        }
+    }
 }
 
 impl From<AST> for SymbolTable {
     fn from(ast: AST) -> Self {
-        let mut table = Self {
-            table: HashMap::new(),
-            n_constr: 0,
-        };
+        let mut table = Self::new(Sense::Max);
 
         if let Some(obj_root) = ast.find_root(ASTNodeTypes::Objective) {
             table.collect_objective_symbols(&ast, obj_root, 1);
@@ -176,6 +343,14 @@ impl From<AST> for SymbolTable {
         if let Some(obj_root) = ast.find_root(ASTNodeTypes::Constraints) {
             table.collect_constraints_symbols(&ast, obj_root);
         }
+        if let Some(bounds_root) = ast.find_root(ASTNodeTypes::Bounds) {
+            table.collect_bounds(&ast, bounds_root);
+        }
+        for root in 0..ast.nodes.len() {
+            if ast.nodes[root].node_type == ASTNodeTypes::Integers {
+                table.collect_integers(&ast, root);
+            }
+        }
         table
     }
 }
@@ -183,9 +358,12 @@ impl From<AST> for SymbolTable {
 #[cfg(test)]
 mod test {
     use crate::{
-        lexer::tokens::F64,
+        lexer::tokens::{CmpOperation, F64},
         parser::ast::construct_ast,
-        semantics::symbols::{Symbol, SymbolTable},
+        semantics::{
+            program::LinearProgram,
+            symbols::{Bounds, Sense, Symbol, SymbolTable},
+        },
     };
 
     #[test]
@@ -202,7 +380,8 @@ st {
             assert!(ast.is_ok());
             let ast = ast.unwrap();
             let t = SymbolTable::from(ast);
-            assert_eq!(t.table.len(), 3);
+            assert_eq!(t.sense, Sense::Max);
+            assert_eq!(t.table.len(), 4);
             assert_eq!(
                 *t.table.get("x1").unwrap(),
                 vec![Symbol::Obj(F64(1.0)), Symbol::Constr(0, F64(-1.21))]
@@ -215,6 +394,13 @@ st {
                 *t.table.get("RHS").unwrap(),
                 vec![Symbol::RHS(0, F64(1000.0)), Symbol::RHS(1, F64(1000.0))]
             );
+            assert_eq!(
+                *t.table.get("CMP").unwrap(),
+                vec![
+                    Symbol::Cmp(0, CmpOperation::Geq),
+                    Symbol::Cmp(1, CmpOperation::Geq)
+                ]
+            );
         }
     }
 
@@ -272,4 +458,114 @@ st {
             assert!(t.n_constr == 3)
         }
     }
+
+    #[test]
+    fn test_symbol_table_sense() {
+        {
+            let input = "
+min {2x1 + 3x2 }
+st {
+    x1 + x2 >= 4
+}
+";
+            let ast = construct_ast(input.as_bytes()).unwrap();
+            let t = SymbolTable::from(ast);
+            assert_eq!(t.sense, Sense::Min);
+            assert_eq!(
+                *t.table.get("x1").unwrap(),
+                vec![Symbol::Obj(F64(2.0)), Symbol::Constr(0, F64(1.0))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_symbol_table_bounds() {
+        let input = "
+max {x_1 + x_2 + y }
+st {
+    x_1 + x_2 <= 50
+}
+bounds {
+    0 <= x_1 <= 40; x_2 free
+    y >= -5
+    y <= 10
+    z = 2
+}
+";
+        let t = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        assert_eq!(t.n_constr, 1);
+        assert_eq!(
+            t.bounds("x_1"),
+            Bounds {
+                lower: 0.0,
+                upper: 40.0
+            }
+        );
+        assert_eq!(t.bounds("x_2"), Bounds::FREE);
+        assert_eq!(
+            t.bounds("y"),
+            Bounds {
+                lower: -5.0,
+                upper: 10.0
+            }
+        );
+        // a variable may appear in the bounds only
+        assert_eq!(t.table["z"], vec![]);
+        assert_eq!(t.bounds("z").upper, 2.0);
+        assert_eq!(t.bounds("w"), Bounds::default());
+    }
+
+    #[test]
+    fn test_symbol_table_integers() {
+        let input = "
+max {x_1 + x_2 + y }
+st {
+    x_1 + x_2 <= 50
+}
+bounds { x_1 <= 40; y <= 5 }
+int { x_1, z }
+bin { y }
+";
+        let t = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        assert!(t.integers.contains("x_1"));
+        assert!(!t.integers.contains("x_2"));
+        assert_eq!(t.bounds("x_1").upper, 40.0);
+        // a binary overrides the bounds section
+        assert!(t.integers.contains("y"));
+        assert_eq!(
+            t.bounds("y"),
+            Bounds {
+                lower: 0.0,
+                upper: 1.0
+            }
+        );
+        assert_eq!(t.table["z"], vec![]);
+        assert_eq!(t.integers.len(), 3);
+    }
+
+    #[test]
+    fn test_symbol_table_reserved() {
+        let input = "
+max {x1 + y1 }
+st {
+    x1 + 2y1 <= 4
+}
+bounds { y1 <= 1 }
+";
+        let t = SymbolTable::from(construct_ast(input.as_bytes()).unwrap());
+        assert!(t.check_names().is_ok());
+        for (from, to) in [("x1", "RHS"), ("y1", "CMP")] {
+            let renamed = input.replace(from, to);
+            let t = SymbolTable::from(construct_ast(renamed.as_bytes()).unwrap());
+            let err = LinearProgram::try_from(&t).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("variable name '{}' is reserved", to)
+            );
+        }
+        // a name in the bounds only is caught as well
+        let bounds = input.replace("y1 <= 1", "RHS <= 1");
+        let t = SymbolTable::from(construct_ast(bounds.as_bytes()).unwrap());
+        assert!(t.check_names().is_err());
+    }
 }
@@ -0,0 +1,339 @@
+use std::fmt::Write;
+
+use ndarray::{Array, Ix2};
+
+/// Output format of a rendered trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    Text,
+    Markdown,
+    Latex,
+}
+
+impl std::str::FromStr for TraceFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(TraceFormat::Text),
+            "markdown" => Ok(TraceFormat::Markdown),
+            "latex" => Ok(TraceFormat::Latex),
+            _ => anyhow::bail!(
+                "unknown trace format '{}', expected text, markdown or latex",
+                s
+            ),
+        }
+    }
+}
+
+/// Objective a traced tableau is optimized for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// the auxiliary objective of Phase I
+    One,
+    /// the objective of the program
+    Two,
+    /// the objective with the artificial columns penalized by M
+    BigM,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::One => write!(f, "Phase 1"),
+            Phase::Two => write!(f, "Phase 2"),
+            Phase::BigM => write!(f, "Big-M"),
+        }
+    }
+}
+
+/// One tableau of the iteration trace. The first step of every phase shows
+/// the starting tableau and has no pivot, a step with an entering column but
+/// no pivot moved that variable to its other bound.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    pub phase: Phase,
+    pub entering: Option<String>,
+    pub leaving: Option<String>,
+    /// pivot element before the pivot was done
+    pub pivot: Option<f64>,
+    /// value of the objective of the phase after the pivot
+    pub objective: f64,
+    /// label of every tableau row: objective row(s) and basic variables
+    pub row_labels: Vec<String>,
+    pub tableau: Array<f64, Ix2>,
+}
+
+/// Every tableau the solver went through, see `SolverOptions::trace`
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// label of every tableau column, the right hand side comes last
+    pub columns: Vec<String>,
+    pub steps: Vec<TraceStep>,
+}
+
+/// Print integral values without decimals, everything else with up to 4.
+/// Values that round to an integer at 4 decimals lose the point, and `-0`
+/// becomes `0`.
+fn format_num(v: f64) -> String {
+    if (v - v.round()).abs() < 1e-9 {
+        format!("{}", v.round() + 0.0)
+    } else {
+        let s = format!("{:.4}", v);
+        match s.trim_end_matches('0').trim_end_matches('.') {
+            "-0" => String::from("0"),
+            s => s.to_string(),
+        }
+    }
+}
+
+/// Escape the characters LaTeX treats specially in text mode
+fn latex_escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '#' | '$' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\textbackslash{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+impl TraceStep {
+    fn title(&self, n: usize) -> String {
+        match (&self.entering, &self.leaving, self.pivot) {
+            (Some(entering), Some(leaving), Some(pivot)) => format!(
+                "{}, step {}: {} enters, {} leaves, pivot {}, objective {}",
+                self.phase,
+                n,
+                entering,
+                leaving,
+                format_num(pivot),
+                format_num(self.objective)
+            ),
+            (Some(entering), None, None) => format!(
+                "{}, step {}: {} moves to its other bound, objective {}",
+                self.phase,
+                n,
+                entering,
+                format_num(self.objective)
+            ),
+            _ => format!(
+                "{}, initial tableau, objective {}",
+                self.phase,
+                format_num(self.objective)
+            ),
+        }
+    }
+
+    /// Cells of the tableau as strings, header first
+    fn cells(&self, columns: &[String]) -> Vec<Vec<String>> {
+        let mut cells = vec![];
+        let mut header = vec![String::from("basis")];
+        header.extend(columns.iter().cloned());
+        cells.push(header);
+        for (i, row) in self.tableau.rows().into_iter().enumerate() {
+            let mut line = vec![self.row_labels[i].clone()];
+            line.extend(row.iter().map(|v| format_num(*v)));
+            cells.push(line);
+        }
+        cells
+    }
+}
+
+impl Trace {
+    pub fn render(&self, format: TraceFormat) -> String {
+        let mut out = String::new();
+        let mut n = 0;
+        for step in &self.steps {
+            // a bound flip is a step as well, only a new phase starts over
+            if step.entering.is_some() {
+                n += 1;
+            } else {
+                n = 0;
+            }
+            let cells = step.cells(&self.columns);
+            match format {
+                TraceFormat::Text => {
+                    let widths = (0..cells[0].len())
+                        .map(|c| cells.iter().map(|r| r[c].len()).max().unwrap_or(0))
+                        .collect::<Vec<_>>();
+                    let _ = writeln!(out, "{}", step.title(n));
+                    for row in &cells {
+                        let line = row
+                            .iter()
+                            .zip(&widths)
+                            .map(|(cell, w)| format!("{:>w$}", cell, w = w))
+                            .collect::<Vec<_>>();
+                        let _ = writeln!(out, "{}", line.join(" ").trim_end());
+                    }
+                }
+                TraceFormat::Markdown => {
+                    let _ = writeln!(out, "**{}**\n", step.title(n));
+                    for (i, row) in cells.iter().enumerate() {
+                        let row = row
+                            .iter()
+                            .map(|c| c.replace('|', "\\|"))
+                            .collect::<Vec<_>>();
+                        let _ = writeln!(out, "| {} |", row.join(" | "));
+                        if i == 0 {
+                            let _ = writeln!(out, "|{}", "---|".repeat(row.len()));
+                        }
+                    }
+                }
+                TraceFormat::Latex => {
+                    let n_cols = cells[0].len();
+                    let _ = writeln!(out, "% {}", step.title(n));
+                    let _ = writeln!(out, "\\begin{{tabular}}{{l|{}|r}}", "r".repeat(n_cols - 2));
+                    for (i, row) in cells.iter().enumerate() {
+                        // labels are text, the numbers get a proper minus sign
+                        let line = row
+                            .iter()
+                            .enumerate()
+                            .map(|(j, c)| match (i, j) {
+                                (0, _) | (_, 0) => latex_escape(c),
+                                _ => format!("${}$", c),
+                            })
+                            .collect::<Vec<_>>();
+                        let _ = writeln!(out, "{} \\\\", line.join(" & "));
+                        if i == 0 {
+                            let _ = writeln!(out, "\\hline");
+                        }
+                    }
+                    let _ = writeln!(out, "\\end{{tabular}}");
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::array;
+
+    use super::{format_num, Phase, Trace, TraceFormat, TraceStep};
+
+    fn trace() -> Trace {
+        Trace {
+            columns: vec!["z", "x_1", "s_0", "rhs"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            steps: vec![
+                TraceStep {
+                    phase: Phase::Two,
+                    entering: None,
+                    leaving: None,
+                    pivot: None,
+                    objective: 0.0,
+                    row_labels: vec![String::from("z"), String::from("s_0")],
+                    tableau: array![[1.0, -3.0, 0.0, 0.0], [0.0, 2.0, 1.0, 4.0]],
+                },
+                TraceStep {
+                    phase: Phase::Two,
+                    entering: Some(String::from("x_1")),
+                    leaving: Some(String::from("s_0")),
+                    pivot: Some(2.0),
+                    objective: 6.0,
+                    row_labels: vec![String::from("z"), String::from("x_1")],
+                    tableau: array![[1.0, 0.0, 1.5, 6.0], [0.0, 1.0, 0.5, 2.0]],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = trace().render(TraceFormat::Text);
+        let truth = "Phase 2, initial tableau, objective 0
+basis z x_1 s_0 rhs
+    z 1  -3   0   0
+  s_0 0   2   1   4
+
+Phase 2, step 1: x_1 enters, s_0 leaves, pivot 2, objective 6
+basis z x_1 s_0 rhs
+    z 1   0 1.5   6
+  x_1 0   1 0.5   2
+
+";
+        assert_eq!(text, truth);
+    }
+
+    #[test]
+    fn test_render_flip() {
+        let mut trace = trace();
+        trace.steps.push(TraceStep {
+            phase: Phase::Two,
+            entering: Some(String::from("s_0")),
+            leaving: None,
+            pivot: None,
+            objective: 6.0,
+            row_labels: vec![String::from("z"), String::from("x_1")],
+            tableau: array![[1.0, 0.0, 1.5, 6.0], [0.0, 1.0, 0.5, 2.0]],
+        });
+        let text = trace.render(TraceFormat::Text);
+        assert!(text.contains("Phase 2, step 1: x_1 enters"));
+        assert!(text.contains("Phase 2, step 2: s_0 moves to its other bound, objective 6\n"));
+        assert!(!text.contains("step 0"));
+    }
+
+    #[test]
+    fn test_format_num() {
+        assert_eq!(format_num(3.0), "3");
+        assert_eq!(format_num(-0.0), "0");
+        assert_eq!(format_num(1.5), "1.5");
+        assert_eq!(format_num(-0.125), "-0.125");
+        assert_eq!(format_num(3.00004), "3");
+        assert_eq!(format_num(2.99996), "3");
+        assert_eq!(format_num(1e-5), "0");
+        assert_eq!(format_num(-1e-5), "0");
+        assert_eq!(format_num(-2.50001), "-2.5");
+        assert_eq!("latex".parse::<TraceFormat>().unwrap(), TraceFormat::Latex);
+        assert!("html".parse::<TraceFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let md = trace().render(TraceFormat::Markdown);
+        assert!(md.contains("| basis | z | x_1 | s_0 | rhs |\n|---|---|---|---|---|\n"));
+        assert!(md.contains("| x_1 | 0 | 1 | 0.5 | 2 |"));
+    }
+
+    #[test]
+    fn test_render_latex() {
+        let tex = trace().render(TraceFormat::Latex);
+        assert!(tex.contains("\\begin{tabular}{l|rrr|r}"));
+        assert!(tex.contains("basis & z & x\\_1 & s\\_0 & rhs \\\\\n\\hline"));
+        assert!(tex.contains("x\\_1 & $0$ & $1$ & $0.5$ & $2$ \\\\"));
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut trace = trace();
+        trace.columns[1] = String::from("a&b%c#d$e^f{g}h~i\\j|k");
+        let tex = trace.render(TraceFormat::Latex);
+        assert!(tex.contains(
+            " & a\\&b\\%c\\#d\\$e\\textasciicircum{}f\\{g\\}h\\textasciitilde{}i\\textbackslash{}j|k & "
+        ));
+        let md = trace.render(TraceFormat::Markdown);
+        assert!(md.contains(
+            "| basis | z | a&b%c#d$e^f{g}h~i\\j\\|k | s_0 | rhs |\n|---|---|---|---|---|\n"
+        ));
+
+        trace
+            .steps
+            .iter_mut()
+            .for_each(|step| step.phase = Phase::BigM);
+        let text = trace.render(TraceFormat::Text);
+        assert!(text.starts_with("Big-M, initial tableau, objective 0\n"));
+        assert!(text.contains("Big-M, step 1: "));
+    }
+}
@@ -0,0 +1,29 @@
+use crate::semantics::sparse::SparseMatrix;
+
+/// Assert that `a` and `b` agree up to the rounding of a few pivots
+pub fn assert_close(a: f64, b: f64) {
+    assert_within(a, b, 1e-6);
+}
+
+/// Assert that `a` and `b` differ by less than `tol`
+pub fn assert_within(a: f64, b: f64, tol: f64) {
+    assert!((a - b).abs() < tol, "{} != {}", a, b);
+}
+
+/// Sparse matrix of dense rows with `n_cols` entries each
+pub fn from_dense(n_cols: usize, rows: &[Vec<f64>]) -> SparseMatrix {
+    let entries = rows.iter().enumerate().flat_map(|(i, row)| {
+        assert_eq!(row.len(), n_cols, "row {} has the wrong length", i);
+        row.iter().enumerate().map(move |(j, a)| (i, j, *a))
+    });
+    SparseMatrix::from_triplets(rows.len(), n_cols, entries)
+}
+
+/// Row `row` of `matrix` with its zeros
+pub fn dense_row(matrix: &SparseMatrix, row: usize) -> Vec<f64> {
+    let mut dense = vec![0.0; matrix.n_cols()];
+    for (j, a) in matrix.row(row) {
+        dense[j] = a;
+    }
+    dense
+}